@@ -2,54 +2,172 @@ use std::fmt::Display;
 
 use crate::{piece::Piece, Position};
 
-/// The board has 8x8 fields.
+/// Maximum number of fields on a board
+pub const MAX_FIELDS: usize = 64;
+
+/// The board has width x height fields, 8x8 by default.
 /// Every field is a u8 to store an id for the current piece on the board
-#[derive(Debug, Clone, Copy, Hash)]
-pub struct Board([u8; 64]);
+///
+/// Next to the ids the board keeps a bit mask of the occupied fields.
+/// The bit of a field is its index `y * width + x`, so placements can be checked with a single AND.
+/// That is why a board can have at most [MAX_FIELDS] fields
+///
+/// Fields can be blocked, to give the board an irregular shape.
+/// Blocked fields stay empty, no piece can be placed on them
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Board {
+    width: u8,
+    height: u8,
+    cells: [u8; MAX_FIELDS],
+    occupied: u64,
+    blocked: u64,
+}
+
+/// Number of symmetries of a square board.
+/// These are the rotations and reflections of the square, the dihedral group D4
+pub const SYMMETRIES: usize = 8;
 
 impl Default for Board {
     fn default() -> Self {
-        Self([0; 64])
+        Self::new(8, 8)
     }
 }
 
 impl Board {
+    /// Creates a new empty board.
+    /// Will validate that the board has between 1 and [MAX_FIELDS] fields
+    pub fn new(width: u8, height: u8) -> Self {
+        let fields = width as usize * height as usize;
+        assert!(fields > 0 && fields <= MAX_FIELDS);
+
+        Self {
+            width,
+            height,
+            cells: [0; MAX_FIELDS],
+            occupied: 0,
+            blocked: 0,
+        }
+    }
+
+    /// Returns the number of fields in x direction
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// Returns the number of fields in y direction
+    pub fn height(&self) -> u8 {
+        self.height
+    }
+
+    /// Returns the number of fields
+    pub fn field_count(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
     /// Gets the raw data of the board
-    pub fn as_slice(&self) -> &[u8; 64] {
-        &self.0
+    pub fn as_slice(&self) -> &[u8] {
+        &self.cells[..self.field_count()]
+    }
+
+    /// Gets the bit mask of all fields that are set to some value
+    pub fn occupied(&self) -> u64 {
+        self.occupied
     }
 
-    /// Return true if all fields are set to some value
+    /// Gets the bit mask of all blocked fields
+    pub fn blocked(&self) -> u64 {
+        self.blocked
+    }
+
+    /// Gets the bit mask of all fields of the board
+    pub fn full_mask(&self) -> u64 {
+        u64::MAX >> (MAX_FIELDS - self.field_count())
+    }
+
+    /// Return true if all fields, that are not blocked, are set to some value
     pub fn is_solved(&self) -> bool {
-        self.0.iter().all(|v| *v != 0)
+        self.occupied | self.blocked == self.full_mask()
+    }
+
+    /// Blocks the field at a given position, so no piece can be placed on it.
+    /// This will remove the old value
+    pub fn block(&mut self, position: Position) {
+        if let Some(index) = self.index(position) {
+            self.set_value(position, 0);
+            self.blocked |= 1 << index;
+        }
+    }
+
+    /// Return true if the field at a given position is blocked
+    pub fn is_blocked(&self, position: Position) -> bool {
+        self.index(position)
+            .is_some_and(|index| self.blocked & 1 << index != 0)
+    }
+
+    /// Return true if the position is on the board and neither set to some value nor blocked
+    pub fn is_free(&self, position: Position) -> bool {
+        self.index(position)
+            .is_some_and(|index| (self.occupied | self.blocked) & 1 << index == 0)
+    }
+
+    /// Return true if the position is on the board
+    pub fn contains(&self, position: Position) -> bool {
+        position.x() < self.width && position.y() < self.height
+    }
+
+    /// Gets the index of a position on the board.
+    ///
+    /// Returns [None] if the position is not on the board
+    pub fn index(&self, position: Position) -> Option<usize> {
+        self.contains(position)
+            .then(|| position.y() as usize * self.width as usize + position.x() as usize)
+    }
+
+    /// Gets the position of a field index
+    pub fn position(&self, index: usize) -> Position {
+        let width = self.width as usize;
+        Position::new((index % width) as u8, (index / width) as u8)
+    }
+
+    /// Iterates over all positions of the board, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..self.field_count()).map(|index| self.position(index))
     }
 
     /// Gets the value at a given position
     pub fn get_value(&self, position: Position) -> u8 {
-        let index = position.y() * 8 + position.x();
-        self.0.get(index as usize).cloned().unwrap_or(0u8)
+        self.index(position)
+            .map(|index| self.cells[index])
+            .unwrap_or(0u8)
     }
 
     /// Sets the value of a given position to new_value.
-    /// This will override the old value
+    /// This will override the old value.
+    /// Blocked fields keep the value 0, writes to them are ignored
     pub fn set_value(&mut self, position: Position, new_value: u8) {
-        let index = position.y() * 8 + position.x();
-        if let Some(val) = self.0.get_mut(index as usize) {
-            *val = new_value;
+        if self.is_blocked(position) {
+            return;
+        }
+        if let Some(index) = self.index(position) {
+            self.cells[index] = new_value;
+            if new_value == 0 {
+                self.occupied &= !(1 << index);
+            } else {
+                self.occupied |= 1 << index;
+            }
         }
     }
 
     /// Places a piece at a given offset.
     ///
-    /// If the piece is not able to be placed at that position it will cancel.
-    pub fn place_piece(&mut self, top_left: Position, piece: &Piece) {
-        if self.can_place_piece(top_left, piece) {
-            for point in piece.points() {
-                // We can unwrap, because we check that in the can_place_piece function
-                self.set_value(top_left.try_add(point).unwrap(), piece.id());
+    /// Returns false and leaves the board unchanged, if the piece can not be placed at that position
+    pub fn place_piece(&mut self, top_left: Position, piece: &Piece) -> bool {
+        match self.piece_mask(top_left, piece) {
+            Some(mask) if self.can_place_mask(mask) => {
+                self.place_mask(mask, piece.id());
+                true
             }
-        } else {
-            println!("Cant place piece!");
+            _ => false,
         }
     }
 
@@ -59,23 +177,172 @@ impl Board {
             .points()
             .iter()
             .all(|point| match top_left.try_add(point) {
-                Some(p) => self.get_value(p) == 0,
+                Some(p) => self.is_free(p),
                 None => false,
             })
     }
 
+    /// Gets the bit mask of the fields covered by the piece placed at top_left.
+    ///
+    /// Returns [None] if the piece does not fit on the board at that position
+    pub fn piece_mask(&self, top_left: Position, piece: &Piece) -> Option<u64> {
+        piece.points().iter().try_fold(0u64, |mask, point| {
+            let index = self.index(top_left.try_add(point)?)?;
+            Some(mask | 1 << index)
+        })
+    }
+
+    /// Check if a placement mask, as created by [Board::piece_mask], is free on the board
+    pub fn can_place_mask(&self, mask: u64) -> bool {
+        (self.occupied | self.blocked) & mask == 0
+    }
+
+    /// Sets all fields of the mask to id.
+    ///
+    /// The mask has to be free on the board, see [Board::can_place_mask]
+    pub fn place_mask(&mut self, mask: u64, id: u8) {
+        debug_assert!(self.can_place_mask(mask));
+        self.occupied |= mask;
+        let mut bits = mask;
+        while bits != 0 {
+            self.cells[bits.trailing_zeros() as usize] = id;
+            bits &= bits - 1;
+        }
+    }
+
+    /// Gets the bit masks of all connected areas of free fields.
+    ///
+    /// Fields are connected if they share an edge. The areas are ordered by their first field
+    pub fn empty_regions(&self) -> Vec<u64> {
+        let width = self.width as usize;
+        let full = self.full_mask();
+        // Fields in the first and the last column, which have no neighbour on one side
+        let first_column = (0..self.height as usize).fold(0u64, |mask, y| mask | 1 << (y * width));
+        let last_column = first_column << (width - 1);
+
+        let mut free = full & !(self.occupied | self.blocked);
+        let mut regions = Vec::new();
+        while free != 0 {
+            let mut region = free & free.wrapping_neg();
+            loop {
+                let grown = region
+                    | (region & !first_column) >> 1
+                    | (region & !last_column) << 1
+                    | region.checked_shr(width as u32).unwrap_or(0)
+                    | region.checked_shl(width as u32).unwrap_or(0);
+                let grown = grown & free;
+                if grown == region {
+                    break;
+                }
+                region = grown;
+            }
+            free &= !region;
+            regions.push(region);
+        }
+        regions
+    }
+
+    /// Applies one of the [SYMMETRIES] to the board.
+    ///
+    /// The symmetries 0 to 3 rotate the board clock wise by 0, 90, 180 and 270 degrees.
+    /// The symmetries 4 to 7 mirror the board on the x axis before rotating it.
+    /// Rotating by 90 or 270 degrees swaps the width and the height
+    pub fn transformed(&self, symmetry: usize) -> Board {
+        let (mut width, mut height) = (self.width, self.height);
+        if symmetry % 2 == 1 {
+            (width, height) = (height, width);
+        }
+        let mut board = Board::new(width, height);
+        for position in self.positions() {
+            let (x, y) = (position.x(), position.y());
+            let (mut t_x, mut t_y) = if symmetry >= 4 {
+                (self.width - 1 - x, y)
+            } else {
+                (x, y)
+            };
+            let mut t_height = self.height;
+            for _ in 0..symmetry % 4 {
+                (t_x, t_y) = (t_height - 1 - t_y, t_x);
+                t_height = if t_height == self.height {
+                    self.width
+                } else {
+                    self.height
+                };
+            }
+            board.set_value(Position::new(t_x, t_y), self.get_value(position));
+            if self.is_blocked(position) {
+                board.block(Position::new(t_x, t_y));
+            }
+        }
+        board
+    }
+
+    /// Gets the symmetries, that keep the shape of the board.
+    ///
+    /// These are all [SYMMETRIES] for a square board.
+    /// A rectangle can only be rotated by 180 degrees and mirrored
+    pub fn symmetries(&self) -> impl Iterator<Item = usize> {
+        let step = if self.width == self.height { 1 } else { 2 };
+        (0..SYMMETRIES).step_by(step)
+    }
+
+    /// Renames the ids in the order they first appear on the board, starting at 1.
+    /// Empty fields stay empty
+    pub fn relabeled(&self) -> Board {
+        let mut labels = [0u8; 256];
+        let mut next_label = 0;
+        let mut board = *self;
+        for value in board.cells.iter_mut().filter(|v| **v != 0) {
+            let label = &mut labels[*value as usize];
+            if *label == 0 {
+                next_label += 1;
+                *label = next_label;
+            }
+            *value = *label;
+        }
+        board
+    }
+
+    /// Gets the canonical form of the board.
+    ///
+    /// Boards that are equal after applying any of their [Board::symmetries] and renaming the ids
+    /// have the same canonical form.
+    /// It is the smallest relabeled board of all symmetries.
+    pub fn canonical(&self) -> Board {
+        self.symmetries()
+            .map(|symmetry| self.transformed(symmetry).relabeled())
+            .min_by_key(|board| (board.cells, board.blocked))
+            .unwrap()
+    }
+
+    /// Return true if the occupied and blocked fields stay the same under all of the [Board::symmetries]
+    pub fn is_symmetric(&self) -> bool {
+        self.symmetries().all(|symmetry| {
+            let transformed = self.transformed(symmetry);
+            transformed.occupied | transformed.blocked == self.occupied | self.blocked
+        })
+    }
+
+    /// Prints the line between two rows
+    fn fmt_separator(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for _ in 0..self.width {
+            write!(f, "+---")?;
+        }
+        writeln!(f, "+")
+    }
+
     /// Prints a single line of the data
     fn fmt_line(&self, f: &mut std::fmt::Formatter<'_>, y: u8) -> std::fmt::Result {
         write!(f, "|")?;
-        for x in 0..8 {
+        for x in 0..self.width {
+            let position = Position::new(x, y);
             write!(
                 f,
                 " {} |",
-                match self.get_value(Position::new(x, y)) {
+                match self.get_value(position) {
+                    0 if self.is_blocked(position) => '#',
                     0 => ' ',
-                    v => {
-                        (b'A' + (v - 1)) as char
-                    }
+                    v => piece_label(v),
                 }
             )?;
         }
@@ -83,33 +350,38 @@ impl Board {
     }
 }
 
+/// Gets the character that shows the piece with the id, like on the printed [Board].
+///
+/// The ids 1 to 26 are the letters A to Z, followed by a to z and the digits 0 to 9.
+/// The higher ids get the letters from 'À' on, so every id has its own character
+pub fn piece_label(id: u8) -> char {
+    assert_ne!(id, 0);
+    ('A'..='Z')
+        .chain('a'..='z')
+        .chain('0'..='9')
+        .chain(('À'..).filter(|c| c.is_alphabetic()))
+        .nth(id as usize - 1)
+        .expect("There are more letters than ids")
+}
+
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "+---+---+---+---+---+---+---+---+")?;
-        self.fmt_line(f, 0)?;
-        writeln!(f, "+---+---+---+---+---+---+---+---+")?;
-        self.fmt_line(f, 1)?;
-        writeln!(f, "+---+---+---+---+---+---+---+---+")?;
-        self.fmt_line(f, 2)?;
-        writeln!(f, "+---+---+---+---+---+---+---+---+")?;
-        self.fmt_line(f, 3)?;
-        writeln!(f, "+---+---+---+---+---+---+---+---+")?;
-        self.fmt_line(f, 4)?;
-        writeln!(f, "+---+---+---+---+---+---+---+---+")?;
-        self.fmt_line(f, 5)?;
-        writeln!(f, "+---+---+---+---+---+---+---+---+")?;
-        self.fmt_line(f, 6)?;
-        writeln!(f, "+---+---+---+---+---+---+---+---+")?;
-        self.fmt_line(f, 7)?;
-        writeln!(f, "+---+---+---+---+---+---+---+---+")
+        self.fmt_separator(f)?;
+        for y in 0..self.height {
+            self.fmt_line(f, y)?;
+            self.fmt_separator(f)?;
+        }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use crate::{piece::Piece, position::Position};
 
-    use super::Board;
+    use super::{piece_label, Board};
 
     #[test]
     fn get_set_value() {
@@ -176,11 +448,143 @@ mod tests {
             let mut board = Board::default();
             board.set_value(Position::new(0, 1), 2);
             assert!(!board.can_place_piece(Position::new(0, 0), &piece));
+            let before = board;
+            assert!(!board.place_piece(Position::new(0, 0), &piece));
+            assert!(!board.place_piece(Position::new(7, 7), &piece));
+            assert_eq!(board, before);
+        }
+    }
+
+    #[test]
+    fn transformed() {
+        let mut board = Board::default();
+        board.set_value(Position::new(1, 0), 1);
+
+        let rotated = board.transformed(1);
+        assert_eq!(rotated.get_value(Position::new(7, 1)), 1);
+        let mirrored = board.transformed(4);
+        assert_eq!(mirrored.get_value(Position::new(6, 0)), 1);
+        assert_eq!(board.transformed(0), board);
+    }
+
+    #[test]
+    fn canonical() {
+        let mut board = Board::default();
+        board.set_value(Position::new(0, 0), 5);
+        board.set_value(Position::new(1, 0), 5);
+        board.set_value(Position::new(2, 0), 3);
+
+        let mut other = Board::default();
+        other.set_value(Position::new(7, 5), 1);
+        other.set_value(Position::new(7, 6), 2);
+        other.set_value(Position::new(7, 7), 2);
+
+        assert_eq!(board.canonical(), other.canonical());
+        assert_ne!(board.canonical(), Board::default().canonical());
+    }
+
+    #[test]
+    fn rectangle() {
+        let mut board = Board::new(3, 2);
+        assert_eq!(board.field_count(), 6);
+        assert!(!board.contains(Position::new(3, 0)));
+        assert!(!board.contains(Position::new(0, 2)));
+
+        board.set_value(Position::new(2, 0), 1);
+        let rotated = board.transformed(1);
+        assert_eq!((rotated.width(), rotated.height()), (2, 3));
+        assert_eq!(rotated.get_value(Position::new(1, 2)), 1);
+        assert_eq!(board.transformed(3).transformed(1), board);
+        assert_eq!(board.symmetries().count(), 4);
+
+        for y in 0..2 {
+            for x in 0..3 {
+                board.set_value(Position::new(x, y), 1);
+            }
+        }
+        assert!(board.is_solved());
+        assert_eq!(
+            board.to_string(),
+            "+---+---+---+\n| A | A | A |\n+---+---+---+\n| A | A | A |\n+---+---+---+\n"
+        );
+    }
+
+    #[test]
+    fn blocked() {
+        let piece = Piece::new(1, vec![Position::new(0, 0), Position::new(1, 0)]);
+        let mut board = Board::new(2, 2);
+        board.block(Position::new(1, 0));
+
+        assert!(board.is_blocked(Position::new(1, 0)));
+        assert!(!board.is_free(Position::new(1, 0)));
+        assert_eq!(board.get_value(Position::new(1, 0)), 0);
+        assert!(!board.can_place_piece(Position::new(0, 0), &piece));
+        assert!(board.can_place_piece(Position::new(0, 1), &piece));
+
+        // A blocked field is never occupied
+        let mut written = board;
+        written.set_value(Position::new(1, 0), 3);
+        assert_eq!(written, board);
+        assert!(!written.is_free(Position::new(1, 0)));
+        assert_eq!(written.occupied(), 0);
+
+        board.place_piece(Position::new(0, 1), &piece);
+        assert!(!board.is_solved());
+        board.set_value(Position::new(0, 0), 2);
+        assert!(board.is_solved(), "Blocked fields are ignored");
+        assert_eq!(
+            board.to_string(),
+            "+---+---+\n| B | # |\n+---+---+\n| A | A |\n+---+---+\n"
+        );
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(piece_label(1), 'A');
+        assert_eq!(piece_label(26), 'Z');
+        assert_eq!(piece_label(27), 'a');
+        assert_eq!(piece_label(53), '0');
+        assert_eq!(piece_label(255), 'Ƃ');
+
+        // Every id has its own character, which can not be mistaken for the grid
+        let labels = (1..=u8::MAX).map(piece_label).collect::<HashSet<_>>();
+        assert_eq!(labels.len(), u8::MAX as usize);
+        assert!(labels.iter().all(|c| !"#|+- ".contains(*c)));
+
+        let mut board = Board::new(2, 1);
+        board.set_value(Position::new(0, 0), 27);
+        board.set_value(Position::new(1, 0), 255);
+        assert_eq!(board.to_string(), "+---+---+\n| a | Ƃ |\n+---+---+\n");
+    }
+
+    #[test]
+    fn empty_regions() {
+        // A wall in the third column splits the board, the second row is not connected to the first row
+        let mut board = Board::new(5, 3);
+        for y in 0..3 {
+            board.block(Position::new(2, y));
         }
+        let piece = Piece::new(1, vec![Position::new(0, 0), Position::new(1, 0)]);
+        board.place_piece(Position::new(3, 1), &piece);
+
+        let regions = board.empty_regions();
+        let sizes = regions.iter().map(|r| r.count_ones()).collect::<Vec<_>>();
+        assert_eq!(sizes, vec![6, 2, 2]);
+        assert_eq!(regions[1], 0b11 << 3);
+        assert_eq!(
+            regions.iter().fold(0, |a, r| a | r),
+            board.full_mask() & !(board.occupied() | board.blocked())
+        );
+
+        assert_eq!(Board::new(1, 1).empty_regions().len(), 1);
+        assert_eq!(Board::new(64, 1).empty_regions(), vec![u64::MAX]);
+        let mut full = Board::new(2, 1);
+        full.place_piece(Position::new(0, 0), &piece);
+        assert!(full.empty_regions().is_empty());
     }
 
     fn test_single_piece_place(mut board: Board, piece: &Piece, position: Position) {
-        board.place_piece(position, &piece);
+        assert!(board.place_piece(position, piece));
         for p in piece.points() {
             assert_eq!(
                 board.get_value(
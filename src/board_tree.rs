@@ -1,44 +1,491 @@
 use std::{
-    collections::HashMap,
-    hash::{DefaultHasher, Hasher},
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    hash::{DefaultHasher, Hash, Hasher},
 };
 
-use crate::board::Board;
+use crate::{board::Board, position::Position};
 
-pub type BoardID = u64;
+pub mod dot;
 
+/// Id of a node in a [BoardTree], numbering the visits of the search
+pub type BoardID = usize;
+
+/// The placement that leads from a node to its child
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// Id of the placed piece
+    pub piece: u8,
+    /// Index of the transform in [Piece::get_all_transforms](crate::piece::Piece::get_all_transforms)
+    pub transform: usize,
+    /// Position of the top left corner of the transform
+    pub position: Position,
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (x, y) = (self.position.x(), self.position.y());
+        write!(f, "piece {} #{} at ({x}, {y})", self.piece, self.transform)
+    }
+}
+
+/// Why the search did not continue after a board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeadEnd {
+    /// An empty field without empty neighbours
+    SingleCell,
+    /// An empty region that the remaining pieces can not fill
+    Region,
+    /// All pieces are placed, but the board is not full
+    NoPiecesLeft,
+    /// The next piece fits nowhere on the board
+    NoPlacement,
+}
+
+impl Display for DeadEnd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeadEnd::SingleCell => write!(f, "Single empty field"),
+            DeadEnd::Region => write!(f, "Region can not be filled"),
+            DeadEnd::NoPiecesLeft => write!(f, "No pieces left"),
+            DeadEnd::NoPlacement => write!(f, "Next piece does not fit"),
+        }
+    }
+}
+
+/// Shape of a [BoardTree]
+#[derive(Debug, Default, PartialEq)]
+pub struct TreeStats {
+    pub nodes: usize,
+    /// Nodes without children
+    pub leaves: usize,
+    pub dead_ends: usize,
+    /// Nodes with a board that an earlier node already has
+    pub transpositions: usize,
+    /// Number of nodes at every depth, starting with the roots
+    pub nodes_per_depth: Vec<usize>,
+}
+
+impl TreeStats {
+    pub fn max_depth(&self) -> usize {
+        self.nodes_per_depth.len().saturating_sub(1)
+    }
+
+    /// Average number of children of the nodes that have children
+    pub fn average_branching(&self) -> f64 {
+        let roots = self.nodes_per_depth.first().copied().unwrap_or(0);
+        let inner = self.nodes - self.leaves;
+        if inner == 0 {
+            return 0.0;
+        }
+        (self.nodes - roots) as f64 / inner as f64
+    }
+}
+
+impl Display for TreeStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Nodes: {}", self.nodes)?;
+        writeln!(f, "Leaves: {}", self.leaves)?;
+        writeln!(f, "Dead ends: {}", self.dead_ends)?;
+        writeln!(f, "Transpositions: {}", self.transpositions)?;
+        writeln!(f, "Max depth: {}", self.max_depth())?;
+        writeln!(f, "Average branching: {:.2}", self.average_branching())
+    }
+}
+
+/// Trace of a search. Every board the search visited is a node, with the board it was placed on as parent.
+///
+/// The id of a node is the number of the visit, so a board that is visited twice has two nodes.
+/// Nodes with the same board can be found with [BoardTree::visits].
+///
+/// A tree with a capacity only keeps the most recent nodes.
+/// When a node is dropped, its children become roots.
 #[derive(Default)]
 pub struct BoardTree {
-    children: HashMap<BoardID, Vec<BoardID>>,
-    parent: HashMap<BoardID, BoardID>,
-    entries: HashMap<BoardID, Board>,
+    capacity: Option<usize>,
+    /// Id of the first node that is still kept
+    first: BoardID,
+    nodes: VecDeque<Node>,
+    /// Sorted by id, so the oldest node is the first root
+    roots: VecDeque<BoardID>,
+    /// Nodes by the hash of their board. Boards with the same hash are told apart by comparing them
+    index: HashMap<u64, Vec<BoardID>>,
+}
+
+struct Node {
+    board: Board,
+    parent: Option<BoardID>,
+    step: Option<Step>,
+    children: Vec<BoardID>,
+    dead_end: Option<DeadEnd>,
+}
+
+fn content_hash(board: &Board) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    board.hash(&mut hasher);
+    hasher.finish()
 }
 
 impl BoardTree {
-    pub fn insert(&mut self, board: Board, parent: Option<BoardID>) -> BoardID {
-        let mut hasher = DefaultHasher::new();
-        hasher.write(board.as_slice());
-        let board_id = hasher.finish();
+    /// Creates a tree that keeps at most the capacity of the most recent nodes
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Default::default()
+        }
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn node(&self, id: BoardID) -> Option<&Node> {
+        self.nodes.get(id.checked_sub(self.first)?)
+    }
+
+    fn node_mut(&mut self, id: BoardID) -> Option<&mut Node> {
+        self.nodes.get_mut(id.checked_sub(self.first)?)
+    }
+
+    /// Adds the board as a root, or as a child reached from the parent by the step.
+    ///
+    /// A child of a parent that is not in the tree, because it was dropped, becomes a root
+    pub fn insert(&mut self, board: Board, parent: Option<(BoardID, Step)>) -> BoardID {
+        let id = self.first + self.nodes.len();
+        let parent_id = parent
+            .map(|(parent, _)| parent)
+            .filter(|parent| self.contains(*parent));
+        match parent_id.and_then(|parent| self.node_mut(parent)) {
+            Some(parent) => parent.children.push(id),
+            None => self.roots.push_back(id),
+        }
+        self.index.entry(content_hash(&board)).or_default().push(id);
+        self.nodes.push_back(Node {
+            board,
+            parent: parent_id,
+            step: parent.map(|(_, step)| step),
+            children: Vec::new(),
+            dead_end: None,
+        });
+
+        self.shrink();
+        id
+    }
+
+    /// Drops the oldest nodes until the tree is in its capacity
+    fn shrink(&mut self) {
+        while self
+            .capacity
+            .is_some_and(|capacity| self.nodes.len() > capacity)
+        {
+            self.drop_oldest();
+        }
+    }
+
+    /// Removes the node that was inserted first.
+    ///
+    /// Its parent is older, so it is a root and its children become roots
+    fn drop_oldest(&mut self) {
+        let Some(oldest) = self.nodes.pop_front() else {
+            return;
+        };
+        let id = self.first;
+        self.first += 1;
+        if self.roots.front() == Some(&id) {
+            self.roots.pop_front();
+        }
+        let hash = content_hash(&oldest.board);
+        if let Some(visits) = self.index.get_mut(&hash) {
+            visits.retain(|visit| *visit != id);
+            if visits.is_empty() {
+                self.index.remove(&hash);
+            }
+        }
+        for child in oldest.children {
+            if let Some(node) = self.node_mut(child) {
+                node.parent = None;
+            }
+            // The children are among the oldest nodes, so they are inserted close to the front
+            let index = self.roots.partition_point(|root| *root < child);
+            self.roots.insert(index, child);
+        }
+    }
+
+    /// Adds all nodes of the other tree after the nodes of this tree, with new ids.
+    ///
+    /// The merged tree has the smaller capacity of both trees and drops its oldest nodes to stay in it
+    pub fn merge(&mut self, other: BoardTree) {
+        self.capacity = match (self.capacity, other.capacity) {
+            (Some(capacity), Some(other)) => Some(capacity.min(other)),
+            (capacity, other) => capacity.or(other),
+        };
+        let next = self.first + self.nodes.len();
+        let new_id = |id: BoardID| id - other.first + next;
+        self.roots.extend(other.roots.into_iter().map(new_id));
+        for (hash, visits) in other.index {
+            let entry = self.index.entry(hash).or_default();
+            entry.extend(visits.into_iter().map(new_id));
+        }
+        self.nodes.extend(other.nodes.into_iter().map(|node| Node {
+            parent: node.parent.map(new_id),
+            children: node.children.into_iter().map(new_id).collect(),
+            ..node
+        }));
+        self.shrink();
+    }
 
-        self.entries.insert(board_id, board);
+    /// Records why the search stopped at the node, if it is in the tree
+    pub fn mark_dead_end(&mut self, id: BoardID, reason: DeadEnd) {
+        if let Some(node) = self.node_mut(id) {
+            node.dead_end = Some(reason);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Gets the nodes without a parent, ordered by their ids
+    pub fn roots(&self) -> &VecDeque<BoardID> {
+        &self.roots
+    }
+
+    pub fn contains(&self, id: BoardID) -> bool {
+        self.node(id).is_some()
+    }
+
+    pub fn get_board(&self, id: BoardID) -> Option<&Board> {
+        self.node(id).map(|node| &node.board)
+    }
+
+    /// Gets every node with the board, in the order they were visited
+    pub fn visits(&self, board: &Board) -> Vec<BoardID> {
+        let Some(visits) = self.index.get(&content_hash(board)) else {
+            return Vec::new();
+        };
+        visits
+            .iter()
+            .copied()
+            .filter(|id| self.get_board(*id) == Some(board))
+            .collect()
+    }
+
+    pub fn parent(&self, id: BoardID) -> Option<BoardID> {
+        self.node(id)?.parent
+    }
+
+    /// Gets the placement that lead from the parent to the node
+    pub fn step(&self, id: BoardID) -> Option<Step> {
+        self.node(id)?.step
+    }
+
+    /// Gets the children of the node, in the order they were visited
+    pub fn children(&self, id: BoardID) -> &[BoardID] {
+        self.node(id).map_or(&[], |node| node.children.as_slice())
+    }
+
+    /// Gets why the search stopped at the node, if it was a dead end
+    pub fn dead_end(&self, id: BoardID) -> Option<DeadEnd> {
+        self.node(id)?.dead_end
+    }
+
+    /// Gets the nodes from the root to the node, including both.
+    ///
+    /// Returns an empty path if the node is not in the tree
+    pub fn path(&self, id: BoardID) -> Vec<BoardID> {
+        if !self.contains(id) {
+            return Vec::new();
+        }
+        let mut path = vec![id];
+        while let Some(parent) = self.parent(*path.last().unwrap()) {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+
+    /// Gets the number of ancestors of the node
+    pub fn depth(&self, id: BoardID) -> usize {
+        self.path(id).len().saturating_sub(1)
+    }
 
-        if let Some(parent) = parent {
-            self.parent.insert(board_id, parent);
-            self.children.entry(parent).or_default().push(board_id);
+    /// Counts the node and all its descendants
+    pub fn subtree_size(&self, id: BoardID) -> usize {
+        if !self.contains(id) {
+            return 0;
+        }
+        let mut size = 0;
+        let mut stack = vec![id];
+        while let Some(node) = stack.pop() {
+            size += 1;
+            stack.extend_from_slice(self.children(node));
         }
+        size
+    }
 
-        board_id
+    /// Collects the shape of the tree, walking down from all roots
+    pub fn stats(&self) -> TreeStats {
+        let mut stats = TreeStats {
+            dead_ends: self.nodes.iter().filter(|n| n.dead_end.is_some()).count(),
+            ..Default::default()
+        };
+        for visits in self.index.values() {
+            let boards = visits.iter().filter_map(|id| self.get_board(*id));
+            stats.transpositions += visits.len() - boards.collect::<HashSet<_>>().len();
+        }
+        let mut level = self.roots.iter().copied().collect::<Vec<_>>();
+        while !level.is_empty() {
+            stats.nodes += level.len();
+            stats.nodes_per_depth.push(level.len());
+            let mut next = Vec::new();
+            for node in level {
+                let children = self.children(node);
+                if children.is_empty() {
+                    stats.leaves += 1;
+                }
+                next.extend_from_slice(children);
+            }
+            level = next;
+        }
+        stats
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Board, piece::Piece, position::Position};
+
+    use super::{BoardTree, DeadEnd, Step};
 
-    fn _get_board(&self, id: BoardID) -> Option<&Board> {
-        self.entries.get(&id)
+    fn step(piece: u8, x: u8) -> Step {
+        Step {
+            piece,
+            transform: 0,
+            position: Position::new(x, 0),
+        }
     }
 
-    fn _parent(&self, id: BoardID) -> Option<BoardID> {
-        self.parent.get(&id).cloned()
+    #[test]
+    fn trace() {
+        let domino = Piece::new(1, vec![Position::new(0, 0), Position::new(1, 0)]);
+        let board = Board::new(4, 2);
+        let mut tree = BoardTree::default();
+        let root = tree.insert(board, None);
+
+        let mut children = Vec::new();
+        for x in 0..3 {
+            let mut child = board;
+            child.place_piece(Position::new(x, 0), &domino);
+            children.push(tree.insert(child, Some((root, step(1, x)))));
+        }
+        let mut grandchild = *tree.get_board(children[0]).unwrap();
+        grandchild.place_piece(
+            Position::new(2, 0),
+            &Piece::new(2, domino.points().to_vec()),
+        );
+        let grandchild = tree.insert(grandchild, Some((children[0], step(2, 2))));
+        tree.mark_dead_end(children[1], DeadEnd::SingleCell);
+
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.roots(), &[root]);
+        assert_eq!(tree.children(root), &children[..]);
+        assert_eq!(tree.path(grandchild), vec![root, children[0], grandchild]);
+        assert_eq!(tree.depth(grandchild), 2);
+        assert_eq!(tree.step(grandchild), Some(step(2, 2)));
+        assert_eq!(tree.step(root), None);
+        assert_eq!(tree.subtree_size(root), 5);
+        assert_eq!(tree.subtree_size(children[0]), 2);
+        assert_eq!(tree.dead_end(children[1]), Some(DeadEnd::SingleCell));
+        assert_eq!(tree.dead_end(children[2]), None);
+
+        let stats = tree.stats();
+        assert_eq!(stats.nodes_per_depth, vec![1, 3, 1]);
+        assert_eq!((stats.nodes, stats.leaves, stats.dead_ends), (5, 3, 1));
+        assert_eq!(stats.max_depth(), 2);
+        assert_eq!(stats.average_branching(), 2.0);
+    }
+
+    #[test]
+    fn capacity() {
+        let monomino = Piece::new(1, vec![Position::new(0, 0)]);
+        let mut tree = BoardTree::with_capacity(3);
+        let mut board = Board::new(4, 1);
+        let mut parent = tree.insert(board, None);
+        let mut nodes = vec![parent];
+        for x in 0..4 {
+            board.place_piece(Position::new(x, 0), &monomino);
+            parent = tree.insert(board, Some((parent, step(1, x))));
+            nodes.push(parent);
+        }
+
+        // The first two boards are dropped, the oldest left one is the new root
+        assert_eq!(tree.len(), 3);
+        assert!(!tree.contains(nodes[1]));
+        assert_eq!(tree.roots(), &[nodes[2]]);
+        assert_eq!(tree.parent(nodes[2]), None);
+        assert_eq!(tree.path(nodes[4]), nodes[2..].to_vec());
+        assert_eq!(tree.step(nodes[2]), Some(step(1, 1)));
+        tree.mark_dead_end(nodes[0], DeadEnd::NoPlacement);
+        assert_eq!(tree.stats().dead_ends, 0);
+
+        // Children of a dropped node are sorted in before the newer roots
+        let mut tree = BoardTree::with_capacity(3);
+        let root = tree.insert(board, None);
+        let children = [0, 1].map(|x| tree.insert(board, Some((root, step(1, x)))));
+        let newer = tree.insert(board, None);
+        assert_eq!(tree.roots(), &[children[0], children[1], newer]);
     }
 
-    fn _children(&self, id: BoardID) -> Option<&[BoardID]> {
-        self.children.get(&id).map(Vec::as_slice)
+    #[test]
+    fn transpositions() {
+        let first = Piece::new(1, vec![Position::new(0, 0)]);
+        let second = Piece::new(2, vec![Position::new(0, 0)]);
+        let mut tree = BoardTree::default();
+        let board = Board::new(2, 1);
+        let root = tree.insert(board, None);
+
+        // Both orders of placing the pieces end with the same board, on two nodes
+        let mut leaves = Vec::new();
+        for (a, b) in [(&first, &second), (&second, &first)] {
+            let mut child = board;
+            child.place_piece(Position::new(a.id() - 1, 0), a);
+            let child = tree.insert(child, Some((root, step(a.id(), a.id() - 1))));
+            let mut leaf = *tree.get_board(child).unwrap();
+            leaf.place_piece(Position::new(b.id() - 1, 0), b);
+            leaves.push(tree.insert(leaf, Some((child, step(b.id(), b.id() - 1)))));
+        }
+        assert_ne!(leaves[0], leaves[1]);
+        assert_eq!(tree.visits(tree.get_board(leaves[0]).unwrap()), leaves);
+        assert_eq!(tree.visits(&board), vec![root]);
+        assert_eq!(tree.stats().transpositions, 1);
+        assert_eq!(tree.len(), 5);
+
+        // Merged nodes get new ids after the existing ones
+        let mut merged = BoardTree::default();
+        merged.insert(board, None);
+        merged.merge(tree);
+        assert_eq!(merged.len(), 6);
+        assert_eq!(merged.roots(), &[0, 1]);
+        assert_eq!(merged.path(leaves[1] + 1), vec![1, 4, 5]);
+        assert_eq!(merged.visits(&board), vec![0, 1]);
+
+        // Merging ring traces keeps the merged tree in the capacity
+        let mut merged = BoardTree::default();
+        for _ in 0..3 {
+            let mut ring = BoardTree::with_capacity(2);
+            let root = ring.insert(board, None);
+            let mut child = board;
+            child.place_piece(Position::new(0, 0), &first);
+            ring.insert(child, Some((root, step(1, 0))));
+            merged.merge(ring);
+        }
+        assert_eq!(merged.capacity(), Some(2));
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged.roots(), &[4]);
+        assert_eq!(merged.children(4), &[5]);
     }
 }
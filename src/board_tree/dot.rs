@@ -0,0 +1,224 @@
+//! Export of a [BoardTree] as a [Graphviz](https://graphviz.org) DOT graph
+
+use std::{collections::HashSet, fmt::Display};
+
+use super::{BoardID, BoardTree, DeadEnd};
+
+/// Number of nodes exported by default, more are hard to render
+const DEFAULT_MAX_NODES: usize = 500;
+
+/// Which part of the tree is exported
+#[derive(Clone, Copy, Debug)]
+pub struct DotOptions {
+    /// Nodes deeper than this are left out, the roots have depth 0
+    pub max_depth: Option<usize>,
+    /// Stop after this many nodes. Nodes closer to the roots are exported first
+    pub max_nodes: Option<usize>,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            max_nodes: Some(DEFAULT_MAX_NODES),
+        }
+    }
+}
+
+impl DotOptions {
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    /// Exports the whole tree, which can be too big to render
+    pub fn unlimited(mut self) -> Self {
+        self.max_depth = None;
+        self.max_nodes = None;
+        self
+    }
+}
+
+/// DOT graph of a [BoardTree], created by [BoardTree::dot]
+pub struct Dot<'a> {
+    tree: &'a BoardTree,
+    options: DotOptions,
+}
+
+impl BoardTree {
+    /// Displays the tree as DOT graph.
+    ///
+    /// Every node shows its board, every edge the placed piece.
+    /// Dead ends are coloured by their [DeadEnd] reason and solved boards are green.
+    /// Nodes with children that were left out because of the limits have a dashed border.
+    pub fn dot(&self, options: DotOptions) -> Dot<'_> {
+        Dot {
+            tree: self,
+            options,
+        }
+    }
+}
+
+fn fill_color(dead_end: DeadEnd) -> &'static str {
+    match dead_end {
+        DeadEnd::SingleCell => "lightcoral",
+        DeadEnd::Region => "orange",
+        DeadEnd::NoPiecesLeft => "lightgray",
+        DeadEnd::NoPlacement => "khaki",
+    }
+}
+
+/// Escapes the text for a DOT string, with every line left aligned
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\l"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Dot<'_> {
+    /// Picks the nodes to export level by level, until a limit is reached
+    fn nodes(&self) -> Vec<BoardID> {
+        let max_nodes = self.options.max_nodes.unwrap_or(usize::MAX);
+        let mut nodes = Vec::new();
+        let mut seen = HashSet::new();
+        let mut level = self.tree.roots().iter().copied().collect::<Vec<_>>();
+        let mut depth = 0;
+        while !level.is_empty() && self.options.max_depth.is_none_or(|max| depth <= max) {
+            let mut next = Vec::new();
+            for node in level {
+                if nodes.len() == max_nodes {
+                    return nodes;
+                }
+                if seen.insert(node) {
+                    nodes.push(node);
+                    next.extend_from_slice(self.tree.children(node));
+                }
+            }
+            level = next;
+            depth += 1;
+        }
+        nodes
+    }
+}
+
+impl Display for Dot<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nodes = self.nodes();
+        let included = nodes.iter().copied().collect::<HashSet<_>>();
+
+        writeln!(f, "digraph search {{")?;
+        writeln!(f, "  node [shape=box, fontname=\"monospace\"];")?;
+        writeln!(f, "  edge [fontname=\"monospace\"];")?;
+        for &node in &nodes {
+            let Some(board) = self.tree.get_board(node) else {
+                continue;
+            };
+            let mut label = board.to_string();
+            let mut styles = Vec::new();
+            let mut color = None;
+            if let Some(dead_end) = self.tree.dead_end(node) {
+                label.push_str(&format!("{dead_end}\n"));
+                color = Some(fill_color(dead_end));
+            } else if board.is_solved() {
+                color = Some("palegreen");
+            }
+            if color.is_some() {
+                styles.push("filled");
+            }
+            let children = self.tree.children(node);
+            if children.iter().any(|child| !included.contains(child)) {
+                styles.push("dashed");
+            }
+
+            write!(f, "  n{node} [label=\"{}\"", escape(&label))?;
+            if !styles.is_empty() {
+                write!(f, ", style=\"{}\"", styles.join(","))?;
+            }
+            if let Some(color) = color {
+                write!(f, ", fillcolor={color}")?;
+            }
+            writeln!(f, "];")?;
+        }
+        for &node in &nodes {
+            let (Some(parent), Some(step)) = (self.tree.parent(node), self.tree.step(node)) else {
+                continue;
+            };
+            if included.contains(&parent) {
+                writeln!(
+                    f,
+                    "  n{parent} -> n{node} [label=\"{}\"];",
+                    escape(&step.to_string())
+                )?;
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::Board,
+        board_tree::{BoardTree, DeadEnd, Step},
+        piece::Piece,
+        position::Position,
+    };
+
+    use super::DotOptions;
+
+    #[test]
+    fn export() {
+        let domino = Piece::new(1, vec![Position::new(0, 0), Position::new(1, 0)]);
+        let board = Board::new(2, 2);
+        let mut tree = BoardTree::default();
+        let root = tree.insert(board, None);
+        let step = |y| Step {
+            piece: 1,
+            transform: 0,
+            position: Position::new(0, y),
+        };
+        let mut child = board;
+        child.place_piece(Position::new(0, 0), &domino);
+        let child = tree.insert(child, Some((root, step(0))));
+        let mut gap = board;
+        gap.place_piece(Position::new(0, 1), &domino);
+        let gap = tree.insert(gap, Some((root, step(1))));
+        tree.mark_dead_end(gap, DeadEnd::SingleCell);
+
+        let dot = tree.dot(DotOptions::default()).to_string();
+        assert!(dot.starts_with("digraph search {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains(&format!(
+            "n{root} -> n{child} [label=\"piece 1 #0 at (0, 0)\"];"
+        )));
+        assert!(dot.contains(&format!(
+            "n{root} -> n{gap} [label=\"piece 1 #0 at (0, 1)\"];"
+        )));
+        assert!(dot.contains("+---+---+\\l|   |   |\\l"));
+        assert!(dot.contains("Single empty field\\l\", style=\"filled\", fillcolor=lightcoral];"));
+
+        // Only the root is left, with a dashed border for its hidden children
+        let dot = tree
+            .dot(DotOptions::default().with_max_depth(0))
+            .to_string();
+        assert!(dot.contains(&format!("n{root} [label=")));
+        assert!(dot.contains("style=\"dashed\""));
+        assert!(!dot.contains("->"));
+        let dot = tree
+            .dot(DotOptions::default().with_max_nodes(2))
+            .to_string();
+        assert_eq!(dot.matches("->").count(), 1);
+    }
+}
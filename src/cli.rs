@@ -0,0 +1,663 @@
+use std::{
+    fmt::Display,
+    path::PathBuf,
+    process::ExitCode,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use tetris_2::{
+    board::piece_label,
+    board_tree::{dot::DotOptions, BoardTree},
+    get_game_pieces,
+    solve::{
+        cell_solver::CellSolver,
+        count_distinct_solutions_with_options, count_solutions_with_options,
+        dlx_solver::DlxSolver,
+        dumb_solver::{DumbSolver, Trace},
+        limits::Limits,
+        observer::{Observer, Progress},
+        parallel_solver::ParallelSolver,
+        solve_with_options, Enumerable, Solvable, SolveOptions,
+    },
+    Board, Piece, Puzzle,
+};
+
+const USAGE: &str = "\
+Usage: tetris_2 <command> [puzzle-file] [options]
+
+Commands:
+  solve    Solve the puzzle and print the solved board
+  count    Count the solutions of the puzzle
+  show     Print the board and all transforms of every piece
+  bench    Measure how long it takes to solve the puzzle
+
+Without a puzzle file the 13 pieces of the game on the 8x8 board are used.
+
+Options that do not apply to the command are rejected.
+
+Options:
+  --solver <dumb|dlx|cell>  Solver to use, defaults to dlx. bench uses all if not set
+  --limit <n>               Stop counting after n solutions
+  --distinct                Only count solutions that are not rotations or reflections of each other
+  --runs <n>                Number of runs for bench, defaults to 5
+  --timeout <seconds>       Give up solving after the time
+  --max-nodes <n>           Give up solving after visiting n nodes of the search
+  --progress                Show the progress of solve and count on stderr
+  --threads <n>             Solve and count on n threads
+  --dot <file>              Write the search tree of the dumb solver as Graphviz DOT graph to the file
+  --transpositions <n>      Let the dumb or cell solver skip up to n boards that were a dead end before
+
+Exit codes:
+  0  The puzzle was solved, or has at least one solution
+  1  The puzzle has no solution, or solving or counting was given up because of --timeout or --max-nodes
+  2  Invalid arguments or puzzle file";
+
+/// Exit code if the puzzle has no solution
+const EXIT_UNSOLVED: u8 = 1;
+/// Exit code for invalid arguments or puzzle files
+const EXIT_INVALID: u8 = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Solve,
+    Count,
+    Show,
+    Bench,
+}
+
+/// Every option, the ones that do not apply to a command are rejected
+const OPTIONS: [&str; 10] = [
+    "--solver",
+    "--limit",
+    "--distinct",
+    "--runs",
+    "--timeout",
+    "--max-nodes",
+    "--progress",
+    "--threads",
+    "--dot",
+    "--transpositions",
+];
+
+impl Command {
+    /// Returns the options that have an effect on the command
+    fn options(&self) -> &'static [&'static str] {
+        match self {
+            Command::Solve => &[
+                "--solver",
+                "--timeout",
+                "--max-nodes",
+                "--progress",
+                "--threads",
+                "--dot",
+                "--transpositions",
+            ],
+            Command::Count => &[
+                "--solver",
+                "--limit",
+                "--distinct",
+                "--timeout",
+                "--max-nodes",
+                "--progress",
+                "--threads",
+                "--dot",
+                "--transpositions",
+            ],
+            Command::Show => &[],
+            Command::Bench => &[
+                "--solver",
+                "--runs",
+                "--timeout",
+                "--max-nodes",
+                "--transpositions",
+            ],
+        }
+    }
+}
+
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Command::Solve => write!(f, "solve"),
+            Command::Count => write!(f, "count"),
+            Command::Show => write!(f, "show"),
+            Command::Bench => write!(f, "bench"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SolverKind {
+    Dumb,
+    Dlx,
+    Cell,
+}
+
+impl Display for SolverKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolverKind::Dumb => write!(f, "dumb"),
+            SolverKind::Dlx => write!(f, "dlx"),
+            SolverKind::Cell => write!(f, "cell"),
+        }
+    }
+}
+
+struct Args {
+    command: Command,
+    puzzle: Option<PathBuf>,
+    solver: Option<SolverKind>,
+    limit: Option<usize>,
+    distinct: bool,
+    runs: usize,
+    timeout: Option<Duration>,
+    max_nodes: Option<usize>,
+    progress: bool,
+    threads: Option<usize>,
+    dot: Option<PathBuf>,
+    transpositions: Option<usize>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let command = match args.next().as_deref() {
+            Some("solve") => Command::Solve,
+            Some("count") => Command::Count,
+            Some("show") => Command::Show,
+            Some("bench") => Command::Bench,
+            Some(command) => return Err(format!("Unknown command '{command}'")),
+            None => return Err("Missing command".to_string()),
+        };
+        let mut parsed = Self {
+            command,
+            puzzle: None,
+            solver: None,
+            limit: None,
+            distinct: false,
+            runs: 5,
+            timeout: None,
+            max_nodes: None,
+            progress: false,
+            threads: None,
+            dot: None,
+            transpositions: None,
+        };
+
+        while let Some(arg) = args.next() {
+            if OPTIONS.contains(&arg.as_str()) && !command.options().contains(&arg.as_str()) {
+                return Err(format!("Option '{arg}' can not be used with {command}"));
+            }
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {name}"))
+            };
+            match arg.as_str() {
+                "--solver" => {
+                    parsed.solver = Some(match value("--solver")?.as_str() {
+                        "dumb" => SolverKind::Dumb,
+                        "dlx" => SolverKind::Dlx,
+                        "cell" => SolverKind::Cell,
+                        solver => return Err(format!("Unknown solver '{solver}'")),
+                    })
+                }
+                "--limit" => parsed.limit = Some(parse_number("--limit", &value("--limit")?)?),
+                "--runs" => parsed.runs = parse_number("--runs", &value("--runs")?)?,
+                "--timeout" => {
+                    let value = value("--timeout")?;
+                    let timeout = value
+                        .parse()
+                        .ok()
+                        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                        .ok_or_else(|| format!("Invalid value '{value}' for --timeout"))?;
+                    parsed.timeout = Some(timeout);
+                }
+                "--max-nodes" => {
+                    parsed.max_nodes = Some(parse_number("--max-nodes", &value("--max-nodes")?)?)
+                }
+                "--distinct" => parsed.distinct = true,
+                "--progress" => parsed.progress = true,
+                "--threads" => {
+                    parsed.threads = Some(parse_number("--threads", &value("--threads")?)?)
+                }
+                "--dot" => parsed.dot = Some(PathBuf::from(value("--dot")?)),
+                "--transpositions" => {
+                    let value = value("--transpositions")?;
+                    parsed.transpositions = Some(parse_number("--transpositions", &value)?)
+                }
+                option if option.starts_with("--") => {
+                    return Err(format!("Unknown option '{option}'"))
+                }
+                path if parsed.puzzle.is_none() => parsed.puzzle = Some(PathBuf::from(path)),
+                path => return Err(format!("Unexpected argument '{path}'")),
+            }
+        }
+        if parsed.dot.is_some() && parsed.solver != Some(SolverKind::Dumb) {
+            return Err("--dot needs --solver dumb".to_string());
+        }
+        if parsed.transpositions.is_some()
+            && !matches!(parsed.solver, Some(SolverKind::Dumb | SolverKind::Cell))
+        {
+            return Err("--transpositions needs --solver dumb or cell".to_string());
+        }
+        Ok(parsed)
+    }
+
+    /// Records the whole search tree of the dumb solver, if it is written with --dot
+    fn trace(&self) -> Trace {
+        if self.dot.is_some() {
+            Trace::Full
+        } else {
+            Trace::Counters
+        }
+    }
+
+    fn dumb_solver(&self) -> DumbSolver {
+        let solver = DumbSolver::new(self.trace());
+        match self.transpositions {
+            Some(capacity) => solver.with_transpositions(capacity),
+            None => solver,
+        }
+    }
+
+    fn cell_solver(&self) -> CellSolver {
+        let solver = CellSolver::default();
+        match self.transpositions {
+            Some(capacity) => solver.with_transpositions(capacity),
+            None => solver,
+        }
+    }
+
+    /// Limits for solving, starting the timeout now
+    fn limits(&self) -> Limits {
+        let mut limits = Limits::default();
+        if let Some(timeout) = self.timeout {
+            limits = limits.with_timeout(timeout);
+        }
+        limits.max_nodes = self.max_nodes;
+        limits
+    }
+}
+
+/// Width of the progress bar in characters
+const PROGRESS_WIDTH: usize = 30;
+/// Minimum time between two redraws of the progress bar
+const PROGRESS_REDRAW: Duration = Duration::from_millis(100);
+
+/// Draws the progress of a search as a single line on stderr
+struct ProgressBar {
+    last_draw: Mutex<Option<Instant>>,
+    solutions: AtomicUsize,
+}
+
+impl ProgressBar {
+    fn new() -> Self {
+        Self {
+            last_draw: Mutex::new(None),
+            solutions: AtomicUsize::new(0),
+        }
+    }
+
+    /// Removes the progress bar, so the result can be printed
+    fn finish(&self) {
+        if self.last_draw.lock().unwrap().is_some() {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
+impl Observer for ProgressBar {
+    fn solution(&self, _board: &Board) {
+        self.solutions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn progress(&self, progress: Progress) {
+        let mut last_draw = self.last_draw.lock().unwrap();
+        if last_draw.is_some_and(|last| last.elapsed() < PROGRESS_REDRAW) {
+            return;
+        }
+        *last_draw = Some(Instant::now());
+
+        let filled = ((progress.explored * PROGRESS_WIDTH as f64) as usize).min(PROGRESS_WIDTH);
+        eprint!(
+            "\r[{}{}] {:5.1}% {} nodes, {} solutions",
+            "#".repeat(filled),
+            ".".repeat(PROGRESS_WIDTH - filled),
+            progress.explored * 100.0,
+            progress.nodes,
+            self.solutions.load(Ordering::Relaxed)
+        );
+    }
+}
+
+fn parse_number(name: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value '{value}' for {name}"))
+}
+
+/// Reads the puzzle file, or returns the game pieces on the default board without a file
+fn load_puzzle(path: Option<&PathBuf>) -> Result<Puzzle, String> {
+    let Some(path) = path else {
+        return Ok(Puzzle {
+            board: Board::default(),
+            pieces: get_game_pieces(),
+        });
+    };
+    let input = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    input
+        .parse()
+        .map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+/// Runs the command line interface with the arguments, without the program name
+pub fn run(args: impl Iterator<Item = String>) -> ExitCode {
+    let args = match Args::parse(args) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}\n\n{USAGE}");
+            return ExitCode::from(EXIT_INVALID);
+        }
+    };
+    let puzzle = match load_puzzle(args.puzzle.as_ref()) {
+        Ok(puzzle) => puzzle,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::from(EXIT_INVALID);
+        }
+    };
+    let pieces = puzzle
+        .pieces
+        .iter()
+        .map(|p| p.get_all_transforms())
+        .collect::<Vec<_>>();
+
+    let progress = args.progress.then(|| Arc::new(ProgressBar::new()));
+    let mut options = SolveOptions::default().with_limits(args.limits());
+    if let Some(progress) = &progress {
+        options = options.with_observer(progress.clone());
+    }
+    let progress = progress.as_deref();
+
+    let board = &puzzle.board;
+    let solver = args.solver.unwrap_or(SolverKind::Dlx);
+    let solved = match args.command {
+        Command::Solve | Command::Count => match (solver, args.threads) {
+            (SolverKind::Dumb, None) => {
+                let solver = args.dumb_solver();
+                let (solved, stats) = run_search(solver, board, &pieces, &args, options, progress);
+                write_dot(args.dot.as_ref(), stats.tree());
+                solved
+            }
+            (SolverKind::Dlx, None) => {
+                run_search(DlxSolver, board, &pieces, &args, options, progress).0
+            }
+            (SolverKind::Cell, None) => {
+                run_search(args.cell_solver(), board, &pieces, &args, options, progress).0
+            }
+            (SolverKind::Dumb, Some(threads)) => {
+                let solver = ParallelSolver::new(args.dumb_solver()).with_threads(threads);
+                let (solved, stats) = run_search(solver, board, &pieces, &args, options, progress);
+                write_dot(args.dot.as_ref(), stats.workers.tree());
+                solved
+            }
+            (SolverKind::Dlx, Some(threads)) => {
+                let solver = ParallelSolver::new(DlxSolver).with_threads(threads);
+                run_search(solver, board, &pieces, &args, options, progress).0
+            }
+            (SolverKind::Cell, Some(threads)) => {
+                let solver = ParallelSolver::new(args.cell_solver()).with_threads(threads);
+                run_search(solver, board, &pieces, &args, options, progress).0
+            }
+        },
+        Command::Show => {
+            show(board, &pieces);
+            true
+        }
+        Command::Bench => {
+            let solvers = match args.solver {
+                Some(solver) => vec![solver],
+                None => vec![SolverKind::Dumb, SolverKind::Dlx, SolverKind::Cell],
+            };
+            solvers.into_iter().fold(true, |solved, solver| {
+                let bench_solved = match solver {
+                    SolverKind::Dumb => {
+                        run_bench(args.dumb_solver(), solver, board, &pieces, &args)
+                    }
+                    SolverKind::Dlx => run_bench(DlxSolver, solver, board, &pieces, &args),
+                    SolverKind::Cell => {
+                        run_bench(args.cell_solver(), solver, board, &pieces, &args)
+                    }
+                };
+                solved && bench_solved
+            })
+        }
+    };
+
+    if solved {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_UNSOLVED)
+    }
+}
+
+/// Runs the solve or count command with the solver. Returns if it succeeded and the stats of the solver
+fn run_search<S: Enumerable>(
+    solver: S,
+    board: &Board,
+    pieces: &[Vec<Piece>],
+    args: &Args,
+    options: SolveOptions,
+    progress: Option<&ProgressBar>,
+) -> (bool, S::SolveStats) {
+    if args.command == Command::Solve {
+        run_solve(solver, board, pieces, &options, progress)
+    } else {
+        run_count(solver, board, pieces, args, &options, progress)
+    }
+}
+
+/// Solves the board and prints the solution. Returns true if it was solved
+fn run_solve<S: Solvable>(
+    solver: S,
+    board: &Board,
+    pieces: &[Vec<Piece>],
+    options: &SolveOptions,
+    progress: Option<&ProgressBar>,
+) -> (bool, S::SolveStats) {
+    let start = Instant::now();
+    let (solve_result, stats) = solve_with_options(solver, board, pieces, options);
+    let elapsed = start.elapsed();
+    if let Some(progress) = progress {
+        progress.finish();
+    }
+
+    let solved = match solve_result {
+        Ok(b) => {
+            println!("Board solved!\n{b}");
+            true
+        }
+        Err(f) => {
+            println!("{f}");
+            false
+        }
+    };
+    eprintln!("{stats}");
+    eprintln!("Took {elapsed:?}");
+    (solved, stats)
+}
+
+/// Counts the solutions and prints the count.
+/// Returns true if there is at least one solution and the count was not aborted
+fn run_count<S: Enumerable>(
+    solver: S,
+    board: &Board,
+    pieces: &[Vec<Piece>],
+    args: &Args,
+    options: &SolveOptions,
+    progress: Option<&ProgressBar>,
+) -> (bool, S::SolveStats) {
+    let start = Instant::now();
+    let (count, aborted, stats) = if args.distinct {
+        count_distinct_solutions_with_options(solver, board, pieces, args.limit, options)
+    } else {
+        count_solutions_with_options(solver, board, pieces, args.limit, options)
+    };
+    let elapsed = start.elapsed();
+    if let Some(progress) = progress {
+        progress.finish();
+    }
+
+    println!("{count}");
+    if let Some(aborted) = aborted {
+        println!("{aborted}, the count is partial");
+    }
+    eprintln!("{stats}");
+    eprintln!("Took {elapsed:?}");
+    (count > 0 && aborted.is_none(), stats)
+}
+
+/// Writes the search tree to the file, if one was given
+fn write_dot(path: Option<&PathBuf>, tree: &BoardTree) {
+    let Some(path) = path else {
+        return;
+    };
+    let dot = tree.dot(DotOptions::default()).to_string();
+    if let Err(e) = std::fs::write(path, dot) {
+        eprintln!("Failed to write {}: {e}", path.display());
+    }
+}
+
+/// Prints the board and every transform of every piece
+fn show(board: &Board, pieces: &[Vec<Piece>]) {
+    println!("Board {}x{}:\n{board}", board.width(), board.height());
+    for all_transforms in pieces {
+        // The policy of a piece allows some orientation, so it has a transform
+        let Some(first) = all_transforms.first() else {
+            continue;
+        };
+        let id = first.id();
+        let copies = match first.count() {
+            1 => String::new(),
+            count => format!(", {count} copies"),
+        };
+        println!(
+            "Piece {}{copies} with {} transforms:",
+            piece_label(id),
+            all_transforms.len()
+        );
+        for piece in all_transforms {
+            println!("{piece}");
+        }
+    }
+}
+
+/// Solves the board multiple times and prints the fastest and the average time,
+/// and the stats of the last run to compare the solvers. Returns true if it was solved.
+///
+/// Every run gets the whole timeout of the arguments
+fn run_bench<S: Solvable + Clone>(
+    solver: S,
+    kind: SolverKind,
+    board: &Board,
+    pieces: &[Vec<Piece>],
+    args: &Args,
+) -> bool {
+    let mut times = Vec::new();
+    let mut failure = None;
+    let mut last_stats = None;
+    for _ in 0..args.runs.max(1) {
+        let options = SolveOptions::default().with_limits(args.limits());
+        let start = Instant::now();
+        let (solve_result, stats) = solve_with_options(solver.clone(), board, pieces, &options);
+        times.push(start.elapsed());
+        failure = solve_result.err();
+        last_stats = Some(stats);
+    }
+
+    let fastest = times.iter().min().cloned().unwrap_or_default();
+    let average = times.iter().sum::<Duration>() / times.len() as u32;
+    println!(
+        "{kind}: fastest {fastest:?}, average {average:?} over {} runs",
+        times.len()
+    );
+    if let Some(failure) = &failure {
+        println!("{kind}: {failure}");
+    }
+    if let Some(stats) = last_stats {
+        eprint!("{stats}");
+    }
+    failure.is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use super::{Args, Command, SolverKind};
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn parse_args() {
+        let args = parse(&["count", "puzzle.txt", "--solver", "dumb", "--limit", "3"]).unwrap();
+        assert!(args.command == Command::Count);
+        assert_eq!(args.puzzle, Some(PathBuf::from("puzzle.txt")));
+        assert!(args.solver == Some(SolverKind::Dumb));
+        assert_eq!(args.limit, Some(3));
+        assert!(!args.distinct);
+
+        let args = parse(&["solve", "--timeout", "1.5", "--max-nodes", "100"]).unwrap();
+        assert_eq!(args.timeout, Some(Duration::from_millis(1500)));
+        assert_eq!(args.max_nodes, Some(100));
+
+        let args = parse(&["count", "--threads", "4"]).unwrap();
+        assert_eq!(args.threads, Some(4));
+
+        let args = parse(&["solve", "--dot", "tree.dot", "--solver", "dumb"]).unwrap();
+        assert_eq!(args.dot, Some(PathBuf::from("tree.dot")));
+
+        let args = parse(&["count", "--solver", "cell", "--transpositions", "1000"]).unwrap();
+        assert_eq!(args.transpositions, Some(1000));
+        let args = parse(&["solve", "--solver", "dumb", "--transpositions", "10"]).unwrap();
+        assert_eq!(args.dumb_solver().transpositions, Some(10));
+
+        let args = parse(&["bench", "--runs", "2", "--max-nodes", "10"]).unwrap();
+        assert!(args.command == Command::Bench);
+        assert_eq!(args.puzzle, None);
+        assert!(args.solver.is_none());
+        assert_eq!(args.runs, 2);
+    }
+
+    #[test]
+    fn parse_invalid_args() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["play"]).is_err());
+        assert!(parse(&["solve", "--solver", "smart"]).is_err());
+        assert!(parse(&["solve", "--solver"]).is_err());
+        assert!(parse(&["count", "--limit", "many"]).is_err());
+        assert!(parse(&["solve", "a.txt", "b.txt"]).is_err());
+        assert!(parse(&["show", "--verbose"]).is_err());
+        assert!(parse(&["solve", "--timeout", "-1"]).is_err());
+        assert!(parse(&["solve", "--dot", "tree.dot"]).is_err());
+        assert!(parse(&["count", "--transpositions", "10", "--solver", "dlx"]).is_err());
+
+        // Options that the command would ignore
+        assert_eq!(
+            parse(&["bench", "--distinct"]).err().unwrap(),
+            "Option '--distinct' can not be used with bench"
+        );
+        assert!(parse(&["solve", "--limit", "3"]).is_err());
+        assert!(parse(&["solve", "--runs", "3"]).is_err());
+        assert!(parse(&["count", "--runs", "3"]).is_err());
+        assert!(parse(&["show", "--solver", "dlx"]).is_err());
+        assert!(parse(&["bench", "--progress"]).is_err());
+        assert!(parse(&["bench", "--threads", "2"]).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+//! Solver for packing puzzles, where pieces have to be placed on a board until every field is covered.
+//!
+//! A [Puzzle] can be parsed from text, its pieces are expanded with [Piece::get_all_transforms]
+//! and then solved with any [Solvable](solve::Solvable), like [DumbSolver](solve::dumb_solver::DumbSolver)
+//! or [DlxSolver](solve::dlx_solver::DlxSolver).
+//!
+//! Standard piece sets, like all pentominoes, are generated by [polyomino::polyominoes].
+
+pub mod board;
+pub mod board_tree;
+pub mod orientation;
+pub mod piece;
+pub mod polyomino;
+pub mod position;
+pub mod puzzle;
+pub mod solution;
+pub mod solve;
+pub mod verify;
+
+pub use board::Board;
+pub use orientation::Orientation;
+pub use piece::Piece;
+pub use position::Position;
+pub use puzzle::Puzzle;
+pub use solution::Solution;
+
+/// The 13 pieces of the game, that fill the default 8x8 board
+pub fn get_game_pieces() -> Vec<Piece> {
+    vec![
+        Piece::new(
+            1,
+            vec![
+                Position::new(1, 0),
+                Position::new(0, 1),
+                Position::new(1, 1),
+                Position::new(1, 2),
+                Position::new(2, 2),
+            ],
+        ),
+        Piece::new(
+            2,
+            vec![
+                Position::new(2, 0),
+                Position::new(0, 1),
+                Position::new(1, 1),
+                Position::new(2, 1),
+                Position::new(0, 2),
+            ],
+        ),
+        Piece::new(
+            3,
+            vec![
+                Position::new(0, 0),
+                Position::new(2, 0),
+                Position::new(0, 1),
+                Position::new(1, 1),
+                Position::new(2, 1),
+                Position::new(0, 2),
+                Position::new(2, 2),
+            ],
+        ),
+        Piece::new(
+            4,
+            vec![
+                Position::new(0, 0),
+                Position::new(0, 1),
+                Position::new(0, 2),
+                Position::new(1, 2),
+                Position::new(2, 2),
+            ],
+        ),
+        Piece::new(
+            5,
+            vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(2, 0),
+                Position::new(0, 1),
+                Position::new(2, 1),
+            ],
+        ),
+        Piece::new(
+            6,
+            vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(1, 1),
+                Position::new(2, 1),
+                Position::new(3, 1),
+            ],
+        ),
+        Piece::new(
+            7,
+            vec![
+                Position::new(1, 0),
+                Position::new(0, 1),
+                Position::new(1, 1),
+                Position::new(2, 1),
+            ],
+        ),
+        Piece::new(
+            8,
+            vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(0, 1),
+                Position::new(1, 1),
+            ],
+        ),
+        Piece::new(
+            9,
+            vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(1, 1),
+                Position::new(2, 1),
+            ],
+        ),
+        Piece::new(
+            10,
+            vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(2, 0),
+                Position::new(3, 0),
+                Position::new(1, 1),
+            ],
+        ),
+        Piece::new(
+            11,
+            vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(2, 0),
+                Position::new(3, 0),
+                Position::new(0, 1),
+            ],
+        ),
+        Piece::new(
+            12,
+            vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(2, 0),
+                Position::new(0, 1),
+            ],
+        ),
+        Piece::new(
+            13,
+            vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(2, 0),
+                Position::new(0, 1),
+                Position::new(1, 1),
+                Position::new(2, 1),
+            ],
+        ),
+    ]
+}
@@ -0,0 +1,207 @@
+use std::fmt::Display;
+
+/// A rotation or reflection of a piece, one of the 8 elements of the dihedral group D4.
+///
+/// The piece is first mirrored on the x axis, if it is flipped, and then rotated clock wise.
+/// The [index](Orientation::index) is numbered like the symmetries of [Board::transformed](crate::Board::transformed)
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Orientation {
+    flipped: bool,
+    rotation: u8,
+}
+
+impl Orientation {
+    /// The orientation that keeps the piece as it is
+    pub const IDENTITY: Self = Self::new(0, false);
+
+    /// All orientations, ordered by their index
+    pub const ALL: [Self; 8] = [
+        Self::new(0, false),
+        Self::new(1, false),
+        Self::new(2, false),
+        Self::new(3, false),
+        Self::new(0, true),
+        Self::new(1, true),
+        Self::new(2, true),
+        Self::new(3, true),
+    ];
+
+    /// Creates the orientation that rotates by quarter turns clock wise, after mirroring if flipped
+    pub const fn new(quarter_turns: u8, flipped: bool) -> Self {
+        Self {
+            flipped,
+            rotation: quarter_turns % 4,
+        }
+    }
+
+    /// Gets the orientation with the index, see [Orientation::ALL].
+    ///
+    /// Returns [None] if the index is 8 or larger
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// Returns the index in [Orientation::ALL]
+    pub fn index(&self) -> usize {
+        self.rotation as usize + if self.flipped { 4 } else { 0 }
+    }
+
+    /// Returns the number of clock wise quarter turns
+    pub fn quarter_turns(&self) -> u8 {
+        self.rotation
+    }
+
+    /// Returns true if the piece is mirrored on the x axis
+    pub fn is_flipped(&self) -> bool {
+        self.flipped
+    }
+
+    /// Returns the orientation of applying self first and then other
+    pub fn then(&self, other: Self) -> Self {
+        // Mirroring after a rotation is the same as mirroring before the opposite rotation
+        let rotation = if other.flipped {
+            other.rotation + 4 - self.rotation
+        } else {
+            other.rotation + self.rotation
+        };
+        Self::new(rotation, self.flipped != other.flipped)
+    }
+
+    /// Returns the orientation that undoes self
+    pub fn inverse(&self) -> Self {
+        if self.flipped {
+            *self
+        } else {
+            Self::new(4 - self.rotation, false)
+        }
+    }
+
+    /// Applies the orientation to a point around (0, 0).
+    /// The result has to be moved back to positive coords, like [Piece::transform](crate::Piece::transform) does
+    pub fn apply(&self, (x, y): (i8, i8)) -> (i8, i8) {
+        let (mut x, mut y) = if self.flipped { (-x, y) } else { (x, y) };
+        for _ in 0..self.rotation {
+            (x, y) = (-y, x);
+        }
+        (x, y)
+    }
+}
+
+/// The orientations a piece can be placed in
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum OrientationPolicy {
+    /// The piece can be rotated and flipped over
+    #[default]
+    Free,
+    /// The piece can be rotated, but not flipped over, like one-sided polyominoes
+    RotationsOnly,
+    /// The piece can only be placed as it is given
+    Fixed,
+    /// The piece can be placed in the listed orientations
+    Custom(Vec<Orientation>),
+}
+
+impl OrientationPolicy {
+    /// Returns true if the piece can be placed in the orientation
+    pub fn allows(&self, orientation: Orientation) -> bool {
+        match self {
+            OrientationPolicy::Free => true,
+            OrientationPolicy::RotationsOnly => !orientation.is_flipped(),
+            OrientationPolicy::Fixed => orientation == Orientation::IDENTITY,
+            OrientationPolicy::Custom(allowed) => allowed.contains(&orientation),
+        }
+    }
+}
+
+impl Display for Orientation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.flipped {
+            write!(f, "flipped, ")?;
+        }
+        write!(f, "rotated {}", self.rotation as u16 * 90)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{piece::Piece, position::Position};
+
+    use super::{Orientation, OrientationPolicy};
+
+    #[test]
+    fn group() {
+        for a in Orientation::ALL {
+            assert_eq!(Orientation::from_index(a.index()), Some(a));
+            assert_eq!(a.then(a.inverse()), Orientation::IDENTITY);
+            assert_eq!(a.inverse().then(a), Orientation::IDENTITY);
+            assert_eq!(a.then(Orientation::IDENTITY), a);
+            for b in Orientation::ALL {
+                for c in Orientation::ALL {
+                    assert_eq!(a.then(b).then(c), a.then(b.then(c)));
+                }
+            }
+        }
+        let flip = Orientation::new(0, true);
+        let cw = Orientation::new(1, false);
+        assert_eq!(flip.then(cw), Orientation::new(1, true));
+        assert_eq!(cw.then(flip), Orientation::new(3, true));
+        assert_eq!(Orientation::from_index(8), None);
+    }
+
+    #[test]
+    fn compose_on_pieces() {
+        // An asymmetric pentomino, every orientation gives a different shape
+        let f = Piece::new(
+            1,
+            vec![
+                Position::new(1, 0),
+                Position::new(2, 0),
+                Position::new(0, 1),
+                Position::new(1, 1),
+                Position::new(1, 2),
+            ],
+        );
+        for a in Orientation::ALL {
+            assert_eq!(f.transform(a).transform(a.inverse()), f);
+            for b in Orientation::ALL {
+                assert_eq!(f.transform(a).transform(b), f.transform(a.then(b)));
+            }
+        }
+        assert_eq!(f.distinct_orientations().len(), 8);
+        assert_eq!(f.transform(Orientation::new(1, false)), f.rotate_cw());
+        assert_eq!(f.transform(Orientation::new(2, true)), f.flip_y());
+    }
+
+    #[test]
+    fn policies() {
+        let points =
+            |points: &[(u8, u8)]| points.iter().map(|(x, y)| Position::new(*x, *y)).collect();
+        let l = Piece::new(1, points(&[(0, 0), (0, 1), (0, 2), (1, 2)]));
+        assert_eq!(l.get_all_transforms().len(), 8);
+
+        let one_sided = l.clone().with_policy(OrientationPolicy::RotationsOnly);
+        assert_eq!(one_sided.get_all_transforms().len(), 4);
+        assert!(one_sided
+            .distinct_orientations()
+            .iter()
+            .all(|(orientation, _)| !orientation.is_flipped()));
+
+        let fixed = l.clone().with_policy(OrientationPolicy::Fixed);
+        assert_eq!(fixed.get_all_transforms(), vec![fixed.clone()]);
+
+        // The square looks the same in every orientation, so only the first allowed one is kept
+        let square = Piece::new(2, points(&[(0, 0), (1, 0), (0, 1), (1, 1)])).with_policy(
+            OrientationPolicy::Custom(vec![Orientation::new(3, true), Orientation::new(1, false)]),
+        );
+        let orientations = square.distinct_orientations();
+        assert_eq!(orientations.len(), 1);
+        assert_eq!(orientations[0].0, Orientation::new(1, false));
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_policy() {
+        let _ =
+            Piece::new(1, vec![Position::new(0, 0)]).with_policy(OrientationPolicy::Custom(vec![]));
+    }
+}
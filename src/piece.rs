@@ -1,6 +1,9 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops::RangeInclusive};
 
-use crate::Position;
+use crate::{
+    orientation::{Orientation, OrientationPolicy},
+    Position,
+};
 
 // +---+---+---+---+       +---+---+---+---+
 // | X | X | X |   |       | X | X | X |   |
@@ -62,18 +65,90 @@ use crate::Position;
 
 /// A Piece that can be placed to the [Board](crate::Board)
 ///
-/// The id is the value that will be written in the board to differentiate between different pieces
+/// The id is the value that will be written in the board to differentiate between different pieces.
+/// The policy limits the orientations of [Piece::get_all_transforms], so every solver honors it.
+///
+/// A piece can stand for several copies of the same shape, that the solvers do not tell apart.
+/// Every copy is written with its own id, counting up from the id of the piece
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Piece {
     id: u8,
     points: Vec<Position>,
+    policy: OrientationPolicy,
+    count: usize,
 }
 
 impl Piece {
-    /// Creates new piece
-    pub fn new(id: u8, points: Vec<Position>) -> Self {
+    /// Creates new piece.
+    /// The points get sorted, so equal shapes always have equal points.
+    ///
+    /// The coords of the points have to fit in an i8, so the piece can be rotated
+    pub fn new(id: u8, mut points: Vec<Position>) -> Self {
         assert_ne!(id, 0);
-        Self { id, points }
+        assert!(points
+            .iter()
+            .all(|p| p.x() <= i8::MAX as u8 && p.y() <= i8::MAX as u8));
+        points.sort();
+        Self {
+            id,
+            points,
+            policy: OrientationPolicy::Free,
+            count: 1,
+        }
+    }
+
+    /// Sets how many copies of the piece have to be placed.
+    ///
+    /// The ids of all copies have to fit in a u8
+    pub fn with_count(mut self, count: usize) -> Self {
+        assert_ne!(count, 0);
+        assert!(self.id as usize + count - 1 <= u8::MAX as usize);
+        self.count = count;
+        self
+    }
+
+    /// Returns how many copies of the piece have to be placed
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Gets the id of the copy with the index, starting at 0
+    pub fn copy_id(&self, copy: usize) -> u8 {
+        debug_assert!(copy < self.count);
+        self.id + copy as u8
+    }
+
+    /// Gets the ids of all copies
+    pub fn ids(&self) -> RangeInclusive<u8> {
+        self.id..=self.copy_id(self.count - 1)
+    }
+
+    /// Gets the copies after the first one, which keep their ids.
+    ///
+    /// Returns [None] if there is only one copy
+    pub fn later_copies(&self) -> Option<Self> {
+        (self.count > 1).then(|| Self {
+            id: self.id + 1,
+            count: self.count - 1,
+            ..self.clone()
+        })
+    }
+
+    /// Sets the orientations the piece can be placed in.
+    ///
+    /// The policy has to allow at least one orientation
+    pub fn with_policy(mut self, policy: OrientationPolicy) -> Self {
+        assert!(
+            Orientation::ALL.into_iter().any(|o| policy.allows(o)),
+            "The piece has to be placeable in some orientation"
+        );
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the orientations the piece can be placed in
+    pub fn policy(&self) -> &OrientationPolicy {
+        &self.policy
     }
 
     /// Returns id of the piece
@@ -88,95 +163,63 @@ impl Piece {
 
     /// Rotate the piece clock wise
     pub fn rotate_cw(&self) -> Self {
-        self.transform_points(|point| {
-            let x = point.x() as i8;
-            let y = point.y() as i8;
-
-            let x_1 = -y;
-            let y_1 = x;
-
-            (x_1, y_1)
-        })
+        self.transform(Orientation::new(1, false))
     }
 
     /// Rotate the piece counter clock wise
     pub fn rotate_ccw(&self) -> Self {
-        self.transform_points(|point| {
-            let x = point.x() as i8;
-            let y = point.y() as i8;
-
-            let x_1 = y;
-            let y_1 = -x;
-
-            (x_1, y_1)
-        })
+        self.transform(Orientation::new(3, false))
     }
 
     /// Rotate the piece 180 degrees
     pub fn rotate_180(&self) -> Self {
-        self.transform_points(|point| {
-            let x = point.x() as i8;
-            let y = point.y() as i8;
-
-            let x_1 = -x;
-            let y_1 = -y;
-
-            (x_1, y_1)
-        })
+        self.transform(Orientation::new(2, false))
     }
 
     /// Flip the piece on the x axis
     pub fn flip_x(&self) -> Self {
-        self.transform_points(|point| {
-            let x = point.x() as i8;
-            let y = point.y() as i8;
-
-            (-x, y)
-        })
+        self.transform(Orientation::new(0, true))
     }
 
     /// Flip the piece on the y axis
     pub fn flip_y(&self) -> Self {
-        self.transform_points(|point| {
-            let x = point.x() as i8;
-            let y = point.y() as i8;
+        self.transform(Orientation::new(2, true))
+    }
 
-            (x, -y)
-        })
+    /// Rotates and flips the piece
+    pub fn transform(&self, orientation: Orientation) -> Self {
+        self.transform_points(|point| orientation.apply((point.x() as i8, point.y() as i8)))
     }
 
-    /// Get all possible transforms.
-    /// This will filter out duplicates automatically
+    /// Gets the orientations allowed by the policy that give different shapes, with the shape each one gives.
     ///
-    /// - self
-    /// - rotated CW
-    /// - rotated CCW
-    /// - rotated 180
-    /// - and all above flipped on both axis
-    pub fn get_all_transforms(&self) -> Vec<Self> {
-        let all = vec![
-            self.clone(),
-            self.rotate_cw(),
-            self.rotate_180(),
-            self.rotate_ccw(),
-            self.flip_x(),
-            self.flip_x().rotate_cw(),
-            self.flip_x().rotate_180(),
-            self.flip_x().rotate_ccw(),
-            self.flip_y(),
-            self.flip_y().rotate_cw(),
-            self.flip_y().rotate_180(),
-            self.flip_y().rotate_ccw(),
-        ];
-        let mut res: Vec<Piece> = Vec::new();
-        for item in all {
-            if !res.iter().any(|f| f.points == item.points) {
-                res.push(item);
+    /// The first orientation in [Orientation::ALL] is kept for shapes that several orientations give
+    pub fn distinct_orientations(&self) -> Vec<(Orientation, Self)> {
+        let mut res: Vec<(Orientation, Self)> = Vec::new();
+        let allowed = Orientation::ALL
+            .into_iter()
+            .filter(|orientation| self.policy.allows(*orientation));
+        for orientation in allowed {
+            let item = self.transform(orientation);
+            if !res.iter().any(|(_, f)| f.points == item.points) {
+                res.push((orientation, item));
             }
         }
         res
     }
 
+    /// Get all possible transforms.
+    /// This will filter out duplicates automatically
+    ///
+    /// These are the shapes of [Piece::distinct_orientations], so the index of a transform
+    /// can be mapped back to how the piece was rotated and flipped
+    pub fn get_all_transforms(&self) -> Vec<Self> {
+        self.distinct_orientations()
+            .into_iter()
+            .map(|(_, piece)| piece)
+            .collect()
+    }
+
     /// Transform the points and place them back in the unsigned space.
     /// This will pack them as close as possible to the (0, 0)
     fn transform_points<F>(&self, f: F) -> Self
@@ -200,6 +243,8 @@ impl Piece {
         Self {
             id: self.id,
             points,
+            policy: self.policy.clone(),
+            count: self.count,
         }
     }
 
@@ -0,0 +1,260 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    orientation::{Orientation, OrientationPolicy},
+    piece::Piece,
+    position::Position,
+};
+
+/// When two polyominoes count as the same shape
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Equivalence {
+    /// Shapes that can be rotated or flipped into each other are the same, like the 12 pentominoes
+    #[default]
+    Free,
+    /// Shapes that can be rotated into each other are the same, a shape and its mirror image are not
+    OneSided,
+    /// Every orientation is its own shape
+    Fixed,
+}
+
+impl Equivalence {
+    /// Returns the policy that places a shape in exactly the orientations that count as the same shape
+    pub fn policy(&self) -> OrientationPolicy {
+        match self {
+            Equivalence::Free => OrientationPolicy::Free,
+            Equivalence::OneSided => OrientationPolicy::RotationsOnly,
+            Equivalence::Fixed => OrientationPolicy::Fixed,
+        }
+    }
+}
+
+/// The pentominoes by their conventional names, drawn with '/' between the rows
+const PENTOMINOES: [(char, &str); 12] = [
+    ('F', ".XX/XX./.X."),
+    ('I', "XXXXX"),
+    ('L', "X./X./X./XX"),
+    ('N', ".X/.X/XX/X."),
+    ('P', "XX/XX/X."),
+    ('T', "XXX/.X./.X."),
+    ('U', "X.X/XXX"),
+    ('V', "X../X../XXX"),
+    ('W', "X../XX./.XX"),
+    ('X', ".X./XXX/.X."),
+    ('Y', ".X/XX/.X/.X"),
+    ('Z', "XX./.X./.XX"),
+];
+
+/// Generates all polyominoes with size fields, one for every shape under the equivalence.
+///
+/// Every shape is given in its canonical orientation, the one with the smallest sorted points,
+/// and the shapes are sorted by their points. So the result is the same for every call
+pub fn polyominoes(size: u8, equivalence: Equivalence) -> Vec<Vec<Position>> {
+    generate(size, equivalence, usize::MAX).unwrap_or_default()
+}
+
+/// Generates the pieces of all polyominoes with size fields, see [polyominoes].
+///
+/// The ids count up from first_id and every piece gets the policy of the equivalence.
+/// Returns [None] if there are more shapes than ids, without generating all of them
+pub fn polyomino_pieces(size: u8, equivalence: Equivalence, first_id: u8) -> Option<Vec<Piece>> {
+    let ids = u8::MAX as usize - first_id as usize + 1;
+    let shapes = generate(size, equivalence, ids)?;
+    let pieces = shapes
+        .into_iter()
+        .zip(first_id..=u8::MAX)
+        .map(|(points, id)| Piece::new(id, points).with_policy(equivalence.policy()))
+        .collect();
+    Some(pieces)
+}
+
+/// The 12 free pentominoes, ordered by their names from F to Z with the ids 1 to 12
+pub fn pentominoes() -> Vec<Piece> {
+    PENTOMINOES
+        .iter()
+        .zip(1..)
+        .map(|((_, drawing), id)| Piece::new(id, drawn(drawing)))
+        .collect()
+}
+
+/// Gets the conventional name of a pentomino in any orientation.
+///
+/// The name is uppercase for a rotation of the shape in [pentominoes] and lowercase for its mirror image,
+/// so the 18 one-sided pentominoes have different names.
+/// Returns [None] if the points are not a pentomino
+pub fn pentomino_name(points: &[Position]) -> Option<char> {
+    let points = normalized(points.to_vec());
+    PENTOMINOES.iter().find_map(|(name, drawing)| {
+        let piece = Piece::new(1, drawn(drawing));
+        // Rotations come first, so only shapes that need a flip are mirror images
+        let orientation = Orientation::ALL
+            .into_iter()
+            .find(|orientation| piece.transform(*orientation).points() == points)?;
+        if orientation.is_flipped() {
+            Some(name.to_ascii_lowercase())
+        } else {
+            Some(*name)
+        }
+    })
+}
+
+/// Grows the shapes one field at a time, every polyomino is a smaller one with a field added.
+///
+/// Returns [None] as soon as a size has more shapes than the limit,
+/// there are never fewer polyominoes with more fields
+fn generate(size: u8, equivalence: Equivalence, limit: usize) -> Option<Vec<Vec<Position>>> {
+    if size == 0 {
+        return Some(Vec::new());
+    }
+    let mut shapes = vec![vec![Position::new(0, 0)]];
+    for _ in 1..size {
+        let mut grown = BTreeSet::new();
+        for shape in shapes.iter() {
+            // Moved by one, so the fields left of and above the shape have positive coords
+            let moved = shape
+                .iter()
+                .map(|p| Position::new(p.x() + 1, p.y() + 1))
+                .collect::<Vec<_>>();
+            for p in moved.iter() {
+                let neighbors = [
+                    Position::new(p.x() - 1, p.y()),
+                    Position::new(p.x() + 1, p.y()),
+                    Position::new(p.x(), p.y() - 1),
+                    Position::new(p.x(), p.y() + 1),
+                ];
+                for neighbor in neighbors {
+                    if moved.contains(&neighbor) {
+                        continue;
+                    }
+                    let mut points = moved.clone();
+                    points.push(neighbor);
+                    grown.insert(canonical(normalized(points), equivalence));
+                }
+            }
+            if grown.len() > limit {
+                return None;
+            }
+        }
+        shapes = grown.into_iter().collect();
+    }
+    (shapes.len() <= limit).then_some(shapes)
+}
+
+/// Gets the smallest points of all orientations that count as the same shape
+fn canonical(points: Vec<Position>, equivalence: Equivalence) -> Vec<Position> {
+    let piece = Piece::new(1, points).with_policy(equivalence.policy());
+    piece
+        .get_all_transforms()
+        .into_iter()
+        .map(|transform| transform.points().to_vec())
+        .min()
+        .expect("The identity is allowed by every equivalence")
+}
+
+/// Moves the points as close as possible to (0, 0) and sorts them
+fn normalized(mut points: Vec<Position>) -> Vec<Position> {
+    let min_x = points.iter().map(Position::x).min().unwrap_or(0);
+    let min_y = points.iter().map(Position::y).min().unwrap_or(0);
+    for point in points.iter_mut() {
+        *point = Position::new(point.x() - min_x, point.y() - min_y);
+    }
+    points.sort();
+    points
+}
+
+/// Gets the points of a drawing like ".X/XX"
+fn drawn(drawing: &str) -> Vec<Position> {
+    drawing
+        .split('/')
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.chars()
+                .enumerate()
+                .filter(|(_, c)| *c == 'X')
+                .map(move |(x, _)| Position::new(x as u8, y as u8))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::orientation::OrientationPolicy;
+
+    use super::{
+        pentomino_name, pentominoes, polyomino_pieces, polyominoes, Equivalence, PENTOMINOES,
+    };
+
+    #[test]
+    fn counts() {
+        // The number of polyominoes with 0 to 7 fields
+        let expected = [
+            (Equivalence::Free, [0, 1, 1, 2, 5, 12, 35, 108]),
+            (Equivalence::OneSided, [0, 1, 1, 2, 7, 18, 60, 196]),
+            (Equivalence::Fixed, [0, 1, 2, 6, 19, 63, 216, 760]),
+        ];
+        for (equivalence, counts) in expected {
+            for (size, count) in counts.into_iter().enumerate() {
+                let shapes = polyominoes(size as u8, equivalence);
+                assert_eq!(shapes.len(), count, "{equivalence:?} size {size}");
+                assert!(shapes.iter().all(|shape| shape.len() == size));
+                assert!(shapes.windows(2).all(|pair| pair[0] < pair[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn pieces() {
+        let tetrominoes = polyomino_pieces(4, Equivalence::OneSided, 3).unwrap();
+        let ids = tetrominoes.iter().map(|p| p.id()).collect::<Vec<_>>();
+        assert_eq!(ids, [3, 4, 5, 6, 7, 8, 9]);
+        assert!(tetrominoes
+            .iter()
+            .all(|p| p.policy() == &OrientationPolicy::RotationsOnly));
+        let transforms = tetrominoes.iter().map(|p| p.get_all_transforms().len());
+        assert_eq!(transforms.sum::<usize>(), 19);
+
+        assert_eq!(
+            polyomino_pieces(6, Equivalence::Fixed, 40).unwrap().len(),
+            216
+        );
+        assert!(polyomino_pieces(6, Equivalence::Fixed, 41).is_none());
+        // Stops before generating the 4655 free polyominoes with 10 fields
+        assert!(polyomino_pieces(10, Equivalence::Free, 1).is_none());
+    }
+
+    #[test]
+    fn pentomino_names() {
+        let pieces = pentominoes();
+        assert_eq!(pieces.len(), PENTOMINOES.len());
+        let names = pieces
+            .iter()
+            .map(|p| pentomino_name(p.points()).unwrap())
+            .collect::<String>();
+        assert_eq!(names, "FILNPTUVWXYZ");
+        for (piece, name) in pieces.iter().zip(names.chars()) {
+            for transform in piece.get_all_transforms() {
+                let found = pentomino_name(transform.points()).unwrap();
+                assert_eq!(found.to_ascii_uppercase(), name);
+            }
+        }
+
+        let free = polyominoes(5, Equivalence::Free)
+            .iter()
+            .map(|shape| pentomino_name(shape).unwrap().to_ascii_uppercase())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(free.into_iter().collect::<String>(), "FILNPTUVWXYZ");
+        let one_sided = polyominoes(5, Equivalence::OneSided)
+            .iter()
+            .map(|shape| pentomino_name(shape).unwrap())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(
+            one_sided.into_iter().collect::<String>(),
+            "FILNPTUVWXYZflnpyz"
+        );
+
+        let tetromino = &polyominoes(4, Equivalence::Free)[0];
+        assert_eq!(pentomino_name(tetromino), None);
+    }
+}
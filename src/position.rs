@@ -1,5 +1,5 @@
-/// Position of a point in the board.
-/// The coords will always be between 0 and 7
+/// Position of a point in the board or in a piece.
+/// The [Board](crate::Board) checks if a position is inside of it
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Position {
     x: u8,
@@ -8,11 +8,7 @@ pub struct Position {
 
 impl Position {
     /// Creates new position.
-    /// Will validate if the coords are 0 < [x, y] < 8
     pub fn new(x: u8, y: u8) -> Self {
-        assert!(x < 8);
-        assert!(y < 8);
-
         Self { x, y }
     }
 
@@ -28,20 +24,16 @@ impl Position {
 
     /// Trys adding a position to self
     ///
-    /// Returns [None] if the position is not in the range 0..8
+    /// Returns [None] if the coords do not fit in a u8
     pub fn try_add(&self, other: &Self) -> Option<Self> {
-        let x = self.x + other.x;
-        let y = self.y + other.y;
-        if x < 8 && y < 8 {
-            Some(Position { x, y })
-        } else {
-            None
-        }
+        let x = self.x.checked_add(other.x)?;
+        let y = self.y.checked_add(other.y)?;
+        Some(Position { x, y })
     }
 
     /// Will apply the offset to the positions
     ///
-    /// This can be negativ and bigger than 8 afterwords
+    /// This can be negativ and outside of the board afterwords
     pub fn offset(&self, offset: (i8, i8)) -> (i8, i8) {
         let x = self.x as i8;
         let y = self.y as i8;
@@ -0,0 +1,662 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    board::{Board, MAX_FIELDS},
+    orientation::{Orientation, OrientationPolicy},
+    piece::Piece,
+    polyomino::{polyomino_pieces, Equivalence},
+    Position,
+};
+
+// A puzzle is written as sections. Every section starts with a header in square brackets,
+// followed by a grid drawn with 'X' for a field and '.' or ' ' for no field.
+// Lines starting with '#' are comments, empty lines are ignored.
+//
+// # The 8x8 board with a 2x2 hole in the middle
+// [board]
+// XXXXXXXX
+// XXXXXXXX
+// XXXXXXXX
+// XXX..XXX
+// XXX..XXX
+// XXXXXXXX
+// XXXXXXXX
+// XXXXXXXX
+//
+// [piece]
+// .X.
+// XXX
+//
+// The board must be defined exactly once. Fields that are '.' on the board are blocked.
+// Every piece gets the next id, starting at 1.
+//
+// A piece header can have options, written as key=value after the name:
+//
+// # An L tetromino that can not be flipped over
+// [piece orientation=one-sided]
+// X.
+// X.
+// XX
+//
+// The orientation is 'free', 'one-sided' for rotations only, 'fixed' for no rotations,
+// or a list of orientation indices like '0,2,4'. See Orientation::ALL for the indices.
+//
+// The count is the number of copies of the piece, like '[piece count=5]' for 5 equal squares.
+// Every copy gets its own id, so the next piece starts after the ids of all copies.
+//
+// A polyominoes section adds every polyomino with the size as pieces, without a grid:
+//
+// # The 18 one-sided pentominoes
+// [polyominoes size=5 orientation=one-sided]
+//
+// The orientation is 'free', 'one-sided' or 'fixed', and tells which shapes count as the same.
+// It is 'free' without the option. See polyomino::polyominoes for the order of the pieces.
+
+/// A board with the pieces that should be placed on it
+#[derive(Debug)]
+pub struct Puzzle {
+    pub board: Board,
+    pub pieces: Vec<Piece>,
+}
+
+/// Error while parsing a [Puzzle].
+///
+/// Line and column start at 1.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A header that is not closed with ']'
+    UnclosedHeader,
+    /// A header with a name that is not 'board', 'piece' or 'polyominoes'
+    UnknownSection(String),
+    /// An option that the section does not have
+    UnknownOption(String),
+    /// An option with a value that can not be used, like 'orientation=sideways'
+    InvalidOption(String),
+    /// An option that the section needs, like the size of polyominoes
+    MissingOption(String),
+    /// A grid line before the first header
+    MissingHeader,
+    /// A character in a grid that is not 'X', '.' or ' '
+    UnexpectedCharacter(char),
+    /// A grid in a section that is not drawn, like polyominoes
+    UnexpectedGrid,
+    /// A section without any 'X'
+    EmptyGrid,
+    /// A grid that is wider or higher than a u8 can describe,
+    /// or a piece that is longer than both sides of the board
+    GridTooLarge,
+    /// A board with more than [MAX_FIELDS] fields
+    BoardTooLarge(usize),
+    /// A second board section
+    DuplicateBoard,
+    /// More pieces and copies than ids
+    TooManyPieces,
+    /// No board section in the puzzle
+    MissingBoard,
+    /// No piece section in the puzzle
+    MissingPieces,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::UnclosedHeader => write!(f, "Header is not closed with ']'"),
+            ParseErrorKind::UnknownSection(name) => write!(f, "Unknown section '{name}'"),
+            ParseErrorKind::UnknownOption(option) => write!(f, "Unknown option '{option}'"),
+            ParseErrorKind::InvalidOption(option) => write!(f, "Invalid option '{option}'"),
+            ParseErrorKind::MissingOption(key) => write!(f, "Missing option '{key}'"),
+            ParseErrorKind::MissingHeader => write!(f, "Grid without a section header"),
+            ParseErrorKind::UnexpectedCharacter(c) => {
+                write!(f, "Unexpected character '{c}', expected 'X' or '.'")
+            }
+            ParseErrorKind::UnexpectedGrid => write!(f, "Section can not have a grid"),
+            ParseErrorKind::EmptyGrid => write!(f, "Section has no fields"),
+            ParseErrorKind::GridTooLarge => write!(f, "Grid is too large"),
+            ParseErrorKind::BoardTooLarge(fields) => {
+                write!(
+                    f,
+                    "Board has {fields} fields, at most {MAX_FIELDS} are allowed"
+                )
+            }
+            ParseErrorKind::DuplicateBoard => write!(f, "Board is already defined"),
+            ParseErrorKind::TooManyPieces => write!(f, "Too many pieces"),
+            ParseErrorKind::MissingBoard => write!(f, "No board defined"),
+            ParseErrorKind::MissingPieces => write!(f, "No pieces defined"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SectionKind {
+    Board,
+    Piece,
+    Polyominoes,
+}
+
+/// A key=value option in a header
+struct SectionOption<'a> {
+    column: usize,
+    key: &'a str,
+    value: &'a str,
+}
+
+/// A header with the grid lines following it
+struct Section<'a> {
+    kind: SectionKind,
+    /// Line of the header
+    line: usize,
+    options: Vec<SectionOption<'a>>,
+    /// Line number and content of every grid line
+    rows: Vec<(usize, &'a str)>,
+}
+
+impl Section<'_> {
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: 1,
+            kind,
+        }
+    }
+
+    fn option_error(&self, option: &SectionOption, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: option.column,
+            kind,
+        }
+    }
+
+    /// Gets the positions of all 'X' in the grid and the size of the grid
+    fn points(&self) -> Result<(Vec<Position>, (usize, usize)), ParseError> {
+        let mut points = Vec::new();
+        let mut width = 0;
+        for (y, (line, row)) in self.rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    'X' | 'x' => {
+                        if x > u8::MAX as usize || y > u8::MAX as usize {
+                            return Err(self.error(ParseErrorKind::GridTooLarge));
+                        }
+                        points.push(Position::new(x as u8, y as u8));
+                    }
+                    '.' | ' ' => {}
+                    c => {
+                        return Err(ParseError {
+                            line: *line,
+                            column: x + 1,
+                            kind: ParseErrorKind::UnexpectedCharacter(c),
+                        })
+                    }
+                }
+                width = width.max(x + 1);
+            }
+        }
+        if points.is_empty() {
+            return Err(self.error(ParseErrorKind::EmptyGrid));
+        }
+        Ok((points, (width, self.rows.len())))
+    }
+
+    /// Builds the board. Every field without an 'X' is blocked, including the fields right of a shorter line
+    fn board(&self) -> Result<Board, ParseError> {
+        if let Some(option) = self.options.first() {
+            let text = format!("{}={}", option.key, option.value);
+            return Err(self.option_error(option, ParseErrorKind::UnknownOption(text)));
+        }
+        let (points, (width, height)) = self.points()?;
+        if width * height > MAX_FIELDS {
+            return Err(self.error(ParseErrorKind::BoardTooLarge(width * height)));
+        }
+
+        let mut board = Board::new(width as u8, height as u8);
+        for position in board.positions().collect::<Vec<_>>() {
+            if !points.contains(&position) {
+                board.block(position);
+            }
+        }
+        Ok(board)
+    }
+
+    /// Builds the piece, moved as close as possible to (0, 0)
+    fn piece(&self, id: u8) -> Result<Piece, ParseError> {
+        let (points, _) = self.points()?;
+        let min_x = points.iter().map(Position::x).min().unwrap_or(0);
+        let min_y = points.iter().map(Position::y).min().unwrap_or(0);
+        let points = points
+            .into_iter()
+            .map(|p| Position::new(p.x() - min_x, p.y() - min_y))
+            .collect::<Vec<_>>();
+        // The points are rotated as i8
+        let limit = i8::MAX as u8;
+        if points.iter().any(|p| p.x() > limit || p.y() > limit) {
+            return Err(self.error(ParseErrorKind::GridTooLarge));
+        }
+        let mut piece = Piece::new(id, points);
+        for option in self.options.iter() {
+            let text = || format!("{}={}", option.key, option.value);
+            match option.key {
+                "orientation" => {
+                    let Some(policy) = orientation_policy(option.value) else {
+                        return Err(
+                            self.option_error(option, ParseErrorKind::InvalidOption(text()))
+                        );
+                    };
+                    piece = piece.with_policy(policy);
+                }
+                "count" => {
+                    let count = option.value.parse::<usize>().unwrap_or(0);
+                    if count == 0 {
+                        return Err(
+                            self.option_error(option, ParseErrorKind::InvalidOption(text()))
+                        );
+                    }
+                    if id as usize + count - 1 > u8::MAX as usize {
+                        return Err(self.error(ParseErrorKind::TooManyPieces));
+                    }
+                    piece = piece.with_count(count);
+                }
+                _ => return Err(self.option_error(option, ParseErrorKind::UnknownOption(text()))),
+            }
+        }
+        Ok(piece)
+    }
+
+    /// Builds all polyominoes of the size, with ids counting up from id
+    fn polyominoes(&self, id: u8) -> Result<Vec<Piece>, ParseError> {
+        if let Some((line, _)) = self.rows.first() {
+            return Err(ParseError {
+                line: *line,
+                column: 1,
+                kind: ParseErrorKind::UnexpectedGrid,
+            });
+        }
+        let mut size = None;
+        let mut equivalence = Equivalence::Free;
+        for option in self.options.iter() {
+            let text = || format!("{}={}", option.key, option.value);
+            let invalid = || self.option_error(option, ParseErrorKind::InvalidOption(text()));
+            match option.key {
+                "size" => match option.value.parse::<u8>() {
+                    Ok(value) if value > 0 => size = Some(value),
+                    _ => return Err(invalid()),
+                },
+                "orientation" => {
+                    equivalence = match option.value {
+                        "free" => Equivalence::Free,
+                        "one-sided" => Equivalence::OneSided,
+                        "fixed" => Equivalence::Fixed,
+                        _ => return Err(invalid()),
+                    }
+                }
+                _ => return Err(self.option_error(option, ParseErrorKind::UnknownOption(text()))),
+            }
+        }
+        let Some(size) = size else {
+            return Err(self.error(ParseErrorKind::MissingOption("size".into())));
+        };
+        polyomino_pieces(size, equivalence, id)
+            .ok_or_else(|| self.error(ParseErrorKind::TooManyPieces))
+    }
+}
+
+/// Parses the value of the orientation option
+fn orientation_policy(value: &str) -> Option<OrientationPolicy> {
+    match value {
+        "free" => Some(OrientationPolicy::Free),
+        "one-sided" => Some(OrientationPolicy::RotationsOnly),
+        "fixed" => Some(OrientationPolicy::Fixed),
+        list => list
+            .split(',')
+            .map(|index| Orientation::from_index(index.trim().parse().ok()?))
+            .collect::<Option<Vec<_>>>()
+            .map(OrientationPolicy::Custom),
+    }
+}
+
+/// Splits the input in sections
+fn sections(input: &str) -> Result<Vec<Section<'_>>, ParseError> {
+    let mut sections: Vec<Section> = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+        let indent = line.len() - line.trim_start().len();
+        let content = line.trim();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = content.strip_prefix('[') {
+            let Some(name) = header.strip_suffix(']') else {
+                return Err(ParseError {
+                    line: line_number,
+                    column: indent + content.chars().count() + 1,
+                    kind: ParseErrorKind::UnclosedHeader,
+                });
+            };
+            // Column of a word in the header, words are slices of the line
+            let column = |word: &str| word.as_ptr() as usize - line.as_ptr() as usize + 1;
+            let mut words = name.split_whitespace();
+            let kind = match words.next().unwrap_or("") {
+                "board" => SectionKind::Board,
+                "piece" => SectionKind::Piece,
+                "polyominoes" => SectionKind::Polyominoes,
+                name => {
+                    return Err(ParseError {
+                        line: line_number,
+                        column: indent + 2,
+                        kind: ParseErrorKind::UnknownSection(name.to_string()),
+                    })
+                }
+            };
+            let mut options = Vec::new();
+            for word in words {
+                let Some((key, value)) = word.split_once('=') else {
+                    return Err(ParseError {
+                        line: line_number,
+                        column: column(word),
+                        kind: ParseErrorKind::UnknownOption(word.to_string()),
+                    });
+                };
+                options.push(SectionOption {
+                    column: column(word),
+                    key,
+                    value,
+                });
+            }
+            sections.push(Section {
+                kind,
+                line: line_number,
+                options,
+                rows: Vec::new(),
+            });
+        } else {
+            let Some(section) = sections.last_mut() else {
+                return Err(ParseError {
+                    line: line_number,
+                    column: indent + 1,
+                    kind: ParseErrorKind::MissingHeader,
+                });
+            };
+            // Keep the indentation, so the columns in errors match the input
+            section.rows.push((line_number, line.trim_end()));
+        }
+    }
+    Ok(sections)
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut board = None;
+        let mut pieces = Vec::new();
+        // Line of every piece section and the longest side of its pieces
+        let mut sides = Vec::new();
+
+        for section in sections(input)? {
+            match section.kind {
+                SectionKind::Board => {
+                    if board.is_some() {
+                        return Err(section.error(ParseErrorKind::DuplicateBoard));
+                    }
+                    board = Some(section.board()?);
+                }
+                SectionKind::Piece | SectionKind::Polyominoes => {
+                    let next = pieces
+                        .last()
+                        .map_or(1, |p: &Piece| *p.ids().end() as usize + 1);
+                    let id = u8::try_from(next)
+                        .map_err(|_| section.error(ParseErrorKind::TooManyPieces))?;
+                    let added = pieces.len();
+                    if section.kind == SectionKind::Piece {
+                        pieces.push(section.piece(id)?);
+                    } else {
+                        pieces.extend(section.polyominoes(id)?);
+                    }
+                    let side = pieces[added..]
+                        .iter()
+                        .flat_map(|p| p.points())
+                        .fold(0, |side: u8, p| side.max(p.x() + 1).max(p.y() + 1));
+                    sides.push((section.line, side));
+                }
+            }
+        }
+
+        let end = ParseError {
+            line: input.lines().count().max(1),
+            column: 1,
+            kind: ParseErrorKind::MissingBoard,
+        };
+        let Some(board) = board else {
+            return Err(end);
+        };
+        if pieces.is_empty() {
+            return Err(ParseError {
+                kind: ParseErrorKind::MissingPieces,
+                ..end
+            });
+        }
+        // A piece longer than both sides of the board does not fit in any orientation
+        let longest = board.width().max(board.height());
+        if let Some((line, _)) = sides.into_iter().find(|(_, side)| *side > longest) {
+            return Err(ParseError {
+                line,
+                column: 1,
+                kind: ParseErrorKind::GridTooLarge,
+            });
+        }
+        Ok(Puzzle { board, pieces })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        get_game_pieces,
+        orientation::{Orientation, OrientationPolicy},
+        piece::Piece,
+        position::Position,
+    };
+
+    use super::{ParseError, ParseErrorKind, Puzzle};
+
+    fn parse_error(input: &str) -> ParseError {
+        input.parse::<Puzzle>().unwrap_err()
+    }
+
+    #[test]
+    fn default_puzzle() {
+        let puzzle: Puzzle = include_str!("../puzzles/default.txt").parse().unwrap();
+        assert_eq!(puzzle.board, Default::default());
+        assert_eq!(puzzle.pieces, get_game_pieces());
+    }
+
+    #[test]
+    fn board_with_hole() {
+        let puzzle: Puzzle = include_str!("../puzzles/pentomino_hole.txt")
+            .parse()
+            .unwrap();
+        assert_eq!((puzzle.board.width(), puzzle.board.height()), (8, 8));
+        assert!(puzzle.board.is_blocked(Position::new(3, 3)));
+        assert!(puzzle.board.is_blocked(Position::new(4, 4)));
+        assert!(!puzzle.board.is_blocked(Position::new(2, 3)));
+        assert_eq!(puzzle.board.blocked().count_ones(), 4);
+        assert_eq!(puzzle.pieces.len(), 12);
+    }
+
+    #[test]
+    fn pieces_are_moved_to_origin() {
+        let puzzle: Puzzle = "[board]\nXX\n[piece]\n\n...\n.X\n X\n".parse().unwrap();
+        assert_eq!(
+            puzzle.pieces[0].points(),
+            &[Position::new(0, 0), Position::new(0, 1)]
+        );
+        assert_eq!(puzzle.pieces[0].id(), 1);
+    }
+
+    #[test]
+    fn piece_options() {
+        let input = "[board]\nXXXX\n[piece orientation=one-sided]\nXX\n[piece  orientation=fixed]\nX\n[piece orientation=0,5]\nX\n[piece]\nX";
+        let puzzle: Puzzle = input.parse().unwrap();
+        let policies = puzzle.pieces.iter().map(Piece::policy).collect::<Vec<_>>();
+        assert_eq!(
+            policies,
+            [
+                &OrientationPolicy::RotationsOnly,
+                &OrientationPolicy::Fixed,
+                &OrientationPolicy::Custom(vec![
+                    Orientation::new(0, false),
+                    Orientation::new(1, true)
+                ]),
+                &OrientationPolicy::Free,
+            ]
+        );
+
+        let error = parse_error("[board]\nX\n[piece orientation=sideways]\nX");
+        assert_eq!((error.line, error.column), (3, 8));
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::InvalidOption("orientation=sideways".into())
+        );
+        assert_eq!(
+            parse_error("[board]\nX\n[piece orientation=8]\nX").kind,
+            ParseErrorKind::InvalidOption("orientation=8".into())
+        );
+        // A piece has to be placeable in some orientation
+        assert_eq!(
+            parse_error("[board]\nX\n[piece orientation=]\nX").kind,
+            ParseErrorKind::InvalidOption("orientation=".into())
+        );
+        assert_eq!(
+            parse_error("[board]\nX\n[piece color=red]\nX").kind,
+            ParseErrorKind::UnknownOption("color=red".into())
+        );
+        assert_eq!(
+            parse_error("[board]\nX\n[piece one-sided]\nX").kind,
+            ParseErrorKind::UnknownOption("one-sided".into())
+        );
+        let error = parse_error("[board orientation=fixed]\nX\n[piece]\nX");
+        assert_eq!((error.line, error.column), (1, 8));
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::UnknownOption("orientation=fixed".into())
+        );
+    }
+
+    #[test]
+    fn piece_counts() {
+        let input = "[board]\nXXXXX\n[piece count=3]\nX\n[piece]\nXX";
+        let puzzle: Puzzle = input.parse().unwrap();
+        let ids = puzzle.pieces.iter().map(Piece::ids).collect::<Vec<_>>();
+        assert_eq!(ids, [1..=3, 4..=4]);
+
+        assert_eq!(
+            parse_error("[board]\nX\n[piece count=0]\nX").kind,
+            ParseErrorKind::InvalidOption("count=0".into())
+        );
+        assert_eq!(
+            parse_error("[board]\nX\n[piece count=many]\nX").kind,
+            ParseErrorKind::InvalidOption("count=many".into())
+        );
+        assert_eq!(
+            parse_error("[board]\nX\n[piece]\nX\n[piece count=255]\nX").kind,
+            ParseErrorKind::TooManyPieces
+        );
+    }
+
+    #[test]
+    fn polyomino_sections() {
+        let input =
+            "[board]\nXXXX\n[piece]\nXX\n[polyominoes size=4 orientation=one-sided]\n[piece]\nX";
+        let puzzle: Puzzle = input.parse().unwrap();
+        let ids = puzzle.pieces.iter().map(Piece::id).collect::<Vec<_>>();
+        assert_eq!(ids, (1..=9).collect::<Vec<_>>());
+        assert_eq!(puzzle.pieces[1].points().len(), 4);
+        assert_eq!(puzzle.pieces[1].policy(), &OrientationPolicy::RotationsOnly);
+        assert_eq!(puzzle.pieces[8].points().len(), 1);
+
+        let error = parse_error("[board]\nX\n[polyominoes]");
+        assert_eq!((error.line, error.column), (3, 1));
+        assert_eq!(error.kind, ParseErrorKind::MissingOption("size".into()));
+        let error = parse_error("[board]\nX\n[polyominoes size=2]\nXX");
+        assert_eq!((error.line, error.column), (4, 1));
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedGrid);
+        assert_eq!(
+            parse_error("[board]\nX\n[polyominoes size=0]").kind,
+            ParseErrorKind::InvalidOption("size=0".into())
+        );
+        assert_eq!(
+            parse_error("[board]\nX\n[polyominoes size=3 orientation=0,1]").kind,
+            ParseErrorKind::InvalidOption("orientation=0,1".into())
+        );
+        assert_eq!(
+            parse_error("[board]\nX\n[polyominoes size=3 count=2]").kind,
+            ParseErrorKind::UnknownOption("count=2".into())
+        );
+        // There are 369 free polyominoes with 8 fields
+        assert_eq!(
+            parse_error("[board]\nX\n[polyominoes size=8]").kind,
+            ParseErrorKind::TooManyPieces
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = parse_error("[board]\nXX\nX?X\n[piece]\nX");
+        assert_eq!((error.line, error.column), (3, 2));
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedCharacter('?'));
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 2: Unexpected character '?', expected 'X' or '.'"
+        );
+
+        let error = parse_error("# comment\n  XX\n");
+        assert_eq!((error.line, error.column), (2, 3));
+        assert_eq!(error.kind, ParseErrorKind::MissingHeader);
+
+        let error = parse_error("[board]\nX\n[pieces]\nX");
+        assert_eq!((error.line, error.column), (3, 2));
+        assert_eq!(error.kind, ParseErrorKind::UnknownSection("pieces".into()));
+
+        let error = parse_error("[board\nX");
+        assert_eq!(error.kind, ParseErrorKind::UnclosedHeader);
+
+        let error = parse_error("[board]\nX\n[piece]\n...\n");
+        assert_eq!((error.line, error.column), (3, 1));
+        assert_eq!(error.kind, ParseErrorKind::EmptyGrid);
+
+        let error = parse_error("[board]\nXXXXXXXXX\nXXXXXXXXX\nXXXXXXXXX\nXXXXXXXXX\nXXXXXXXXX\nXXXXXXXXX\nXXXXXXXXX\nXXXXXXXXX\n[piece]\nX");
+        assert_eq!(error.kind, ParseErrorKind::BoardTooLarge(72));
+
+        // A piece far wider than any board, which can not be rotated as i8
+        let wide = format!("[piece]\nX{}X\n[board]\nXX", " ".repeat(199));
+        let error = parse_error(&wide);
+        assert_eq!((error.line, error.column), (1, 1));
+        assert_eq!(error.kind, ParseErrorKind::GridTooLarge);
+        // The straight piece is longer than both sides of the board
+        let error = parse_error("[board]\nXXX\nXXX\n[piece]\nX\n[piece]\nXXXX");
+        assert_eq!((error.line, error.column), (6, 1));
+        assert_eq!(error.kind, ParseErrorKind::GridTooLarge);
+        assert!("[board]\nXXX\n[piece]\nX\nX\nX".parse::<Puzzle>().is_ok());
+
+        let error = parse_error("[board]\nX\n[board]\nX\n[piece]\nX");
+        assert_eq!((error.line, error.column), (3, 1));
+        assert_eq!(error.kind, ParseErrorKind::DuplicateBoard);
+
+        assert_eq!(parse_error("[piece]\nX").kind, ParseErrorKind::MissingBoard);
+        assert_eq!(
+            parse_error("[board]\nX").kind,
+            ParseErrorKind::MissingPieces
+        );
+    }
+}
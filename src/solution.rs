@@ -0,0 +1,127 @@
+use std::fmt::Display;
+
+use crate::{board::Board, orientation::Orientation, piece::Piece, position::Position};
+
+/// A transform of a piece placed on the board
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placement {
+    /// Id of the piece
+    pub piece: u8,
+    /// Index of the transform in [Piece::get_all_transforms]
+    pub transform: usize,
+    /// Position of the top left corner of the transform
+    pub anchor: Position,
+    /// Fields covered by the piece
+    pub cells: Vec<Position>,
+}
+
+impl Placement {
+    /// Places the shape, the transform with the index, with its top left corner at the anchor.
+    ///
+    /// Returns [None] if a point of the shape would be outside of the positions
+    pub fn new(shape: &Piece, transform: usize, anchor: Position) -> Option<Self> {
+        let cells = shape
+            .points()
+            .iter()
+            .map(|point| anchor.try_add(point))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            piece: shape.id(),
+            transform,
+            anchor,
+            cells,
+        })
+    }
+
+    /// Gets how the piece was rotated and flipped for this placement.
+    ///
+    /// Returns [None] if the piece has no transform with the index
+    pub fn orientation(&self, piece: &Piece) -> Option<Orientation> {
+        piece
+            .distinct_orientations()
+            .get(self.transform)
+            .map(|(orientation, _)| *orientation)
+    }
+}
+
+impl Display for Placement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (x, y) = (self.anchor.x(), self.anchor.y());
+        write!(f, "piece {} #{} at ({x}, {y})", self.piece, self.transform)
+    }
+}
+
+/// A solved board with the placement of every piece, sorted by the id of the piece
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Solution {
+    pub board: Board,
+    pub placements: Vec<Placement>,
+}
+
+impl Solution {
+    /// Places the pieces of the placements on the empty board
+    pub fn new(board: &Board, mut placements: Vec<Placement>) -> Self {
+        placements.sort_by_key(|placement| placement.piece);
+        Self {
+            board: replay(board, &placements),
+            placements,
+        }
+    }
+}
+
+/// Fills the cells of every placement with the id of its piece
+pub fn replay(board: &Board, placements: &[Placement]) -> Board {
+    let mut board = *board;
+    for placement in placements {
+        for cell in placement.cells.iter() {
+            board.set_value(*cell, placement.piece);
+        }
+    }
+    board
+}
+
+impl Display for Solution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.board)?;
+        for placement in self.placements.iter() {
+            writeln!(f, "{placement}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Board, orientation::Orientation, piece::Piece, position::Position};
+
+    use super::{Placement, Solution};
+
+    #[test]
+    fn replay_placements() {
+        let domino = Piece::new(2, vec![Position::new(0, 0), Position::new(0, 1)]);
+        let bar = Piece::new(1, vec![Position::new(0, 0), Position::new(1, 0)]);
+        let placements = vec![
+            Placement::new(&domino, 1, Position::new(2, 0)).unwrap(),
+            Placement::new(&bar, 0, Position::new(0, 1)).unwrap(),
+        ];
+        assert_eq!(
+            placements[0].cells,
+            vec![Position::new(2, 0), Position::new(2, 1)]
+        );
+        assert!(Placement::new(&domino, 0, Position::new(0, 255)).is_none());
+        assert_eq!(
+            placements[0].orientation(&domino),
+            Some(Orientation::new(1, false))
+        );
+
+        let board = Board::new(3, 2);
+        let solution = Solution::new(&board, placements);
+        assert_eq!(solution.placements[0].piece, 1, "Sorted by piece");
+        assert_eq!(solution.board.get_value(Position::new(1, 1)), 1);
+        assert_eq!(solution.board.get_value(Position::new(2, 0)), 2);
+        assert!(solution.board.is_free(Position::new(0, 0)));
+        assert!(solution
+            .to_string()
+            .ends_with("piece 1 #0 at (0, 1)\npiece 2 #1 at (2, 0)\n"));
+    }
+}
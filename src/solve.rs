@@ -1,15 +1,93 @@
-use std::fmt::Display;
+use std::{collections::HashSet, fmt::Display, sync::Arc};
 
-use crate::{board::Board, piece::Piece};
+use crate::{
+    board::{Board, SYMMETRIES},
+    orientation::Orientation,
+    piece::Piece,
+    position::Position,
+    solution::{Placement, Solution},
+};
 
+pub mod cell_solver;
+pub mod dlx_solver;
 pub mod dumb_solver;
+#[cfg(test)]
 mod empty_solver;
+pub mod limits;
+pub mod observer;
+pub mod parallel_solver;
+pub mod placement_masks;
+pub mod transposition;
 
-pub type SolveResult<T> = Result<Board, T>;
+use limits::{Aborted, Limits};
+use observer::Observer;
+
+pub type SolveResult<T> = Result<Solution, T>;
+
+/// Builds the solution from the piece index, transform and position of every placement.
+///
+/// The solvers do not tell the copies of a piece apart, so the copies are labeled in the order of
+/// their position and transform. This way every solver labels a solution the same way
+fn build_solution(
+    board: &Board,
+    pieces: &[Vec<Piece>],
+    mut placed: Vec<(usize, usize, Position)>,
+) -> Solution {
+    placed.sort_by_key(|(piece, transform, position)| (*piece, board.index(*position), *transform));
+    let mut copies = vec![0; pieces.len()];
+    let placements = placed
+        .into_iter()
+        .map(|(piece, transform, position)| {
+            let shape = &pieces[piece][transform];
+            let mut placement = Placement::new(shape, transform, position).unwrap();
+            placement.piece = shape.copy_id(copies[piece]);
+            copies[piece] += 1;
+            placement
+        })
+        .collect();
+    Solution::new(board, placements)
+}
+
+/// Gets all transforms of the piece with the points, for the tests of the solvers
+#[cfg(test)]
+fn piece(id: u8, points: &[(u8, u8)]) -> Vec<Piece> {
+    let points = points.iter().map(|(x, y)| Position::new(*x, *y)).collect();
+    Piece::new(id, points).get_all_transforms()
+}
+
+/// Gets all transforms of the straight piece with the length, for the tests of the solvers
+#[cfg(test)]
+fn straight(id: u8, length: u8) -> Vec<Piece> {
+    let points = (0..length).map(|x| (x, 0)).collect::<Vec<_>>();
+    piece(id, &points)
+}
+
+/// Limits and observer of a search.
+///
+/// The default has no limits and no observer
+#[derive(Clone, Default)]
+pub struct SolveOptions {
+    pub limits: Limits,
+    pub observer: Option<Arc<dyn Observer + Send + Sync>>,
+}
+
+impl SolveOptions {
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_observer(mut self, observer: Arc<dyn Observer + Send + Sync>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+}
 
 /// Trait for a solvable
 /// Impl this type to invoke solve with it.
-/// It should return a single solved board or an error if it is not solvable
+/// It should return a single solution or an error if it is not solvable.
+/// If one of the limits of the options is reached, it should stop and return an error with the [Aborted].
+/// The observer of the options should be told about every placement
 pub trait Solvable {
     type SolveStats: Default + Display;
     type Failure: Display;
@@ -19,15 +97,742 @@ pub trait Solvable {
         stats: &mut Self::SolveStats,
         board: &Board,
         pieces: &[Vec<Piece>],
+        options: &SolveOptions,
     ) -> SolveResult<Self::Failure>;
 }
 
+/// Trait for a solvable that can find every solution of a board.
+/// Impl this type to invoke solve_all and count_solutions with it.
+pub trait Enumerable: Solvable {
+    type Search<'a>: Search<Stats = Self::SolveStats>;
+
+    /// Prepares a search over all solutions of the board with the options
+    fn search<'a>(
+        &self,
+        stats: &mut Self::SolveStats,
+        board: &Board,
+        pieces: &'a [Vec<Piece>],
+        options: &SolveOptions,
+    ) -> Self::Search<'a>;
+}
+
+/// A search that can be resumed after every solution it finds
+pub trait Search {
+    type Stats;
+
+    /// Continues the search until the next solution.
+    ///
+    /// Returns [None] if there are no more solutions
+    fn next_solution(&mut self, stats: &mut Self::Stats) -> Option<Solution>;
+
+    /// Continues the search until the next solution, without building the solution.
+    ///
+    /// Returns false if there are no more solutions
+    fn skip_solution(&mut self, stats: &mut Self::Stats) -> bool {
+        self.next_solution(stats).is_some()
+    }
+
+    /// Returns why the search stopped, if it stopped because of its limits
+    fn aborted(&self) -> Option<Aborted>;
+}
+
 /// Solves the board with an provided Solvable
 pub fn solve<S: Solvable>(
     solver: S,
     board: &Board,
     pieces: &[Vec<Piece>],
+) -> (SolveResult<S::Failure>, S::SolveStats) {
+    solve_with_options(solver, board, pieces, &SolveOptions::default())
+}
+
+/// Solves the board with an provided Solvable and options.
+///
+/// The stats are returned even if the search was aborted
+pub fn solve_with_options<S: Solvable>(
+    solver: S,
+    board: &Board,
+    pieces: &[Vec<Piece>],
+    options: &SolveOptions,
 ) -> (SolveResult<S::Failure>, S::SolveStats) {
     let mut stats = S::SolveStats::default();
-    (solver.solve(&mut stats, board, pieces), stats)
+    (solver.solve(&mut stats, board, pieces, options), stats)
+}
+
+/// Lazy iterator over all solutions of a board.
+///
+/// The search only runs when the next solution is requested.
+pub struct Solutions<'a, S: Enumerable> {
+    search: S::Search<'a>,
+    stats: S::SolveStats,
+}
+
+impl<'a, S: Enumerable> Solutions<'a, S> {
+    /// Consumes the iterator and returns the stats of the search so far
+    pub fn into_stats(self) -> S::SolveStats {
+        self.stats
+    }
+
+    /// Counts the remaining solutions without building them.
+    ///
+    /// Stops after limit solutions, if a limit is provided
+    pub fn count_remaining(&mut self, limit: Option<usize>) -> usize {
+        let mut count = 0;
+        while limit.is_none_or(|limit| count < limit) && self.search.skip_solution(&mut self.stats)
+        {
+            count += 1;
+        }
+        count
+    }
+
+    /// Returns why the search stopped, if it stopped because of its limits
+    pub fn aborted(&self) -> Option<Aborted> {
+        self.search.aborted()
+    }
+}
+
+impl<'a, S: Enumerable> Iterator for Solutions<'a, S> {
+    type Item = Solution;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.search.next_solution(&mut self.stats)
+    }
+}
+
+/// Finds all solutions of the board with an provided Enumerable
+pub fn solve_all<'a, S: Enumerable>(
+    solver: S,
+    board: &Board,
+    pieces: &'a [Vec<Piece>],
+) -> Solutions<'a, S> {
+    solve_all_with_options(solver, board, pieces, &SolveOptions::default())
+}
+
+/// Finds all solutions of the board with an provided Enumerable and options
+pub fn solve_all_with_options<'a, S: Enumerable>(
+    solver: S,
+    board: &Board,
+    pieces: &'a [Vec<Piece>],
+    options: &SolveOptions,
+) -> Solutions<'a, S> {
+    let mut stats = S::SolveStats::default();
+    let search = solver.search(&mut stats, board, pieces, options);
+    Solutions { search, stats }
+}
+
+/// Counts the solutions of the board with an provided Enumerable.
+///
+/// Stops after limit solutions, if a limit is provided
+pub fn count_solutions<S: Enumerable>(
+    solver: S,
+    board: &Board,
+    pieces: &[Vec<Piece>],
+    limit: Option<usize>,
+) -> (usize, S::SolveStats) {
+    let (count, _, stats) =
+        count_solutions_with_options(solver, board, pieces, limit, &SolveOptions::default());
+    (count, stats)
+}
+
+/// Counts the solutions of the board with an provided Enumerable and options.
+///
+/// If the search is aborted, only the solutions found until then are counted
+/// and the [Aborted] tells why the count is partial
+pub fn count_solutions_with_options<S: Enumerable>(
+    solver: S,
+    board: &Board,
+    pieces: &[Vec<Piece>],
+    limit: Option<usize>,
+    options: &SolveOptions,
+) -> (usize, Option<Aborted>, S::SolveStats) {
+    let mut solutions = solve_all_with_options(solver, board, pieces, options);
+    let count = solutions.count_remaining(limit);
+    (count, solutions.aborted(), solutions.into_stats())
+}
+
+/// Restricts the pieces, so only one of the symmetric variants of every solution can be found.
+///
+/// Every solution of a symmetric board can be rotated and mirrored into a variant
+/// for each of the [Board::symmetries].
+/// If a piece has all 8 transforms, each of these variants has the piece in a different transform.
+/// On a square board keeping only the first transform of that piece leaves exactly one variant.
+/// On a rectangle the first two transforms, which differ by a rotation of 90 degrees, are kept.
+///
+/// Returns [None] if the board is not symmetric, no piece with a single copy has 8 different transforms
+/// or the transforms of a piece are restricted, so a variant of a solution might not be a solution
+pub fn break_symmetry(board: &Board, pieces: &[Vec<Piece>]) -> Option<Vec<Vec<Piece>>> {
+    if !board.is_symmetric() {
+        return None;
+    }
+    // The board symmetries are numbered like the orientations of the pieces
+    let closed = pieces.iter().all(|transforms| {
+        transforms.iter().all(|transform| {
+            board
+                .symmetries()
+                .filter_map(Orientation::from_index)
+                .all(|orientation| {
+                    let moved = transform.transform(orientation);
+                    transforms.iter().any(|t| t.points() == moved.points())
+                })
+        })
+    });
+    if !closed {
+        return None;
+    }
+    // Truncating the transforms of a piece with copies would restrict every copy
+    let asymmetric = pieces
+        .iter()
+        .position(|t| t.len() == SYMMETRIES && t.first().is_some_and(|t| t.count() == 1))?;
+    let mut restricted = pieces.to_vec();
+    restricted[asymmetric].truncate(SYMMETRIES / board.symmetries().count());
+    Some(restricted)
+}
+
+/// Counts the solutions of the board that are distinct under rotating and mirroring the board.
+///
+/// Uses [break_symmetry] if possible. Otherwise all solutions are enumerated and compared
+/// by their [Board::canonical] form.
+/// Stops after limit solutions, if a limit is provided
+pub fn count_distinct_solutions<S: Enumerable>(
+    solver: S,
+    board: &Board,
+    pieces: &[Vec<Piece>],
+    limit: Option<usize>,
+) -> (usize, S::SolveStats) {
+    let (count, _, stats) = count_distinct_solutions_with_options(
+        solver,
+        board,
+        pieces,
+        limit,
+        &SolveOptions::default(),
+    );
+    (count, stats)
+}
+
+/// Counts the distinct solutions of the board with an provided Enumerable and options.
+///
+/// If the search is aborted, only the solutions found until then are counted
+/// and the [Aborted] tells why the count is partial
+pub fn count_distinct_solutions_with_options<S: Enumerable>(
+    solver: S,
+    board: &Board,
+    pieces: &[Vec<Piece>],
+    limit: Option<usize>,
+    options: &SolveOptions,
+) -> (usize, Option<Aborted>, S::SolveStats) {
+    // Congruent pieces can swap their ids, which break_symmetry does not account for
+    let congruent = pieces.iter().enumerate().any(|(i, a)| {
+        a.first().is_some_and(|first| {
+            pieces[i + 1..]
+                .iter()
+                .any(|b| b.iter().any(|t| t.points() == first.points()))
+        })
+    });
+    if !congruent {
+        if let Some(restricted) = break_symmetry(board, pieces) {
+            return count_solutions_with_options(solver, board, &restricted, limit, options);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut solutions = solve_all_with_options(solver, board, pieces, options);
+    while limit.is_none_or(|limit| seen.len() < limit) {
+        let Some(solution) = solutions.next() else {
+            break;
+        };
+        seen.insert(solution.board.canonical());
+    }
+    (seen.len(), solutions.aborted(), solutions.into_stats())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{
+        board::Board, orientation::OrientationPolicy, piece::Piece, position::Position,
+        solution::Solution, verify::verify,
+    };
+
+    use super::{
+        break_symmetry,
+        cell_solver::{CellFailure, CellSolver},
+        count_distinct_solutions, count_distinct_solutions_with_options, count_solutions,
+        count_solutions_with_options,
+        dlx_solver::{DlxFailure, DlxSolver},
+        dumb_solver::{DumbFailure, DumbSolver},
+        limits::{AbortReason, CancelToken, Limits},
+        observer::Observer,
+        parallel_solver::ParallelSolver,
+        piece, solve, solve_all, solve_all_with_options, solve_with_options, straight, Solvable,
+        SolveOptions,
+    };
+
+    /// Board with only a 3x2 area not blocked, to be filled by three dominoes
+    fn domino_puzzle() -> (Board, Vec<Vec<Piece>>) {
+        let mut board = Board::default();
+        for y in 0..8 {
+            for x in 0..8 {
+                if x >= 3 || y >= 2 {
+                    board.block(Position::new(x, y));
+                }
+            }
+        }
+        let pieces = (1..=3)
+            .map(|id| {
+                Piece::new(id, vec![Position::new(0, 0), Position::new(1, 0)]).get_all_transforms()
+            })
+            .collect();
+        (board, pieces)
+    }
+
+    #[test]
+    fn count_all_solutions() {
+        // The 3x2 area has 3 domino tilings, with 3! ways to assign the pieces to each
+        let (board, pieces) = domino_puzzle();
+        assert_eq!(count_solutions(DlxSolver, &board, &pieces, None).0, 18);
+        assert_eq!(
+            count_solutions(DumbSolver::default(), &board, &pieces, None).0,
+            18
+        );
+    }
+
+    #[test]
+    fn every_piece_is_used() {
+        // The first three dominoes fill the board, so the fourth one can not be placed
+        let (board, mut pieces) = domino_puzzle();
+        pieces.push(straight(4, 2));
+        assert_eq!(count_solutions(DlxSolver, &board, &pieces, None).0, 0);
+        assert_eq!(
+            count_solutions(DumbSolver::default(), &board, &pieces, None).0,
+            0
+        );
+        assert_eq!(
+            count_solutions(CellSolver::default(), &board, &pieces, None).0,
+            0
+        );
+        let parallel = ParallelSolver::new(DumbSolver::default()).with_threads(2);
+        assert_eq!(count_solutions(parallel, &board, &pieces, None).0, 0);
+        let (result, _) = solve(DumbSolver::default(), &board, &pieces);
+        assert!(matches!(result, Err(DumbFailure::NotSolvable)));
+    }
+
+    #[test]
+    fn pieces_without_transforms() {
+        // A piece without transforms can not be placed, so there is no solution
+        let (board, mut pieces) = domino_puzzle();
+        pieces.push(Vec::new());
+        assert_eq!(count_solutions(DlxSolver, &board, &pieces, None).0, 0);
+        assert_eq!(
+            count_solutions(DumbSolver::default(), &board, &pieces, None).0,
+            0
+        );
+        assert_eq!(
+            count_solutions(CellSolver::default(), &board, &pieces, None).0,
+            0
+        );
+        let parallel = ParallelSolver::new(DumbSolver::default()).with_threads(2);
+        assert_eq!(count_solutions(parallel, &board, &pieces, None).0, 0);
+        assert_eq!(
+            count_distinct_solutions(DlxSolver, &board, &pieces, None).0,
+            0
+        );
+        let (result, _) = solve(CellSolver::default(), &board, &pieces);
+        assert!(matches!(result, Err(CellFailure::NotSolvable)));
+    }
+
+    #[test]
+    fn copies_are_not_told_apart() {
+        // The 3 tilings of the 3x2 area, each found once with 3 copies of one domino
+        let (board, _) = domino_puzzle();
+        let domino = Piece::new(1, vec![Position::new(0, 0), Position::new(1, 0)]).with_count(3);
+        let pieces = vec![domino.get_all_transforms()];
+
+        let boards = |solutions: Vec<Solution>| {
+            let mut boards = solutions.into_iter().map(|s| s.board).collect::<Vec<_>>();
+            boards.sort_by(|a, b| a.as_slice().cmp(b.as_slice()));
+            boards
+        };
+        let expected = boards(solve_all(DlxSolver, &board, &pieces).collect());
+        assert_eq!(expected.len(), 3);
+        for solved in expected.iter() {
+            assert_eq!(
+                verify(&board, std::slice::from_ref(&domino), solved),
+                Ok(())
+            );
+        }
+
+        let cell = CellSolver::default().with_transpositions(100);
+        assert_eq!(boards(solve_all(cell, &board, &pieces).collect()), expected);
+        let dumb = solve_all(DumbSolver::default(), &board, &pieces);
+        assert_eq!(boards(dumb.collect()), expected);
+        let parallel = ParallelSolver::new(DumbSolver::default()).with_threads(2);
+        assert_eq!(
+            boards(solve_all(parallel, &board, &pieces).collect()),
+            expected
+        );
+    }
+
+    #[test]
+    fn copies_count_like_distinct_pieces() {
+        let board = Board::new(5, 4);
+        let shapes = [
+            (&[(0, 0), (1, 0), (0, 1), (1, 1)][..], 2),
+            (&[(0, 0), (1, 0), (2, 0)], 2),
+            (&[(0, 0), (1, 0)], 3),
+        ];
+        let mut copies = Vec::new();
+        let mut distinct = Vec::new();
+        let mut permutations = 1;
+        for (points, count) in shapes {
+            let points = points
+                .iter()
+                .map(|(x, y)| Position::new(*x, *y))
+                .collect::<Vec<_>>();
+            let id = distinct.len() as u8 + 1;
+            copies.push(Piece::new(id, points.clone()).with_count(count));
+            for copy in 0..count {
+                distinct.push(Piece::new(id + copy as u8, points.clone()).get_all_transforms());
+                permutations *= copy + 1;
+            }
+        }
+        let pieces = copies
+            .iter()
+            .map(Piece::get_all_transforms)
+            .collect::<Vec<_>>();
+
+        let (expected, _) = count_solutions(DlxSolver, &board, &distinct, None);
+        assert!(expected > 0);
+        assert_eq!(
+            count_solutions(DlxSolver, &board, &pieces, None).0 * permutations,
+            expected
+        );
+        let cell = CellSolver::default().with_transpositions(1000);
+        assert_eq!(
+            count_solutions(cell, &board, &pieces, None).0 * permutations,
+            expected
+        );
+        assert_eq!(
+            count_solutions(DumbSolver::default(), &board, &pieces, None).0 * permutations,
+            expected
+        );
+        let parallel = ParallelSolver::new(DlxSolver).with_threads(2);
+        assert_eq!(
+            count_solutions(parallel, &board, &pieces, None).0 * permutations,
+            expected
+        );
+        for solution in solve_all(DlxSolver, &board, &pieces) {
+            assert_eq!(verify(&board, &copies, &solution), Ok(()));
+        }
+    }
+
+    #[test]
+    fn solve_with_blocked_fields() {
+        let (board, pieces) = domino_puzzle();
+        let (result, _) = solve(DumbSolver::default(), &board, &pieces);
+        assert!(result.is_ok_and(|s| s.board.is_solved() && s.board.blocked() == board.blocked()));
+    }
+
+    #[test]
+    fn count_with_limit() {
+        let (board, pieces) = domino_puzzle();
+        assert_eq!(count_solutions(DlxSolver, &board, &pieces, Some(5)).0, 5);
+        assert_eq!(
+            count_solutions(DumbSolver::default(), &board, &pieces, Some(20)).0,
+            18
+        );
+    }
+
+    #[test]
+    fn iterate_solutions() {
+        let (board, pieces) = domino_puzzle();
+        let solutions = solve_all(DlxSolver, &board, &pieces).collect::<Vec<_>>();
+        assert_eq!(solutions.len(), 18);
+        assert!(solutions.iter().all(|s| s.board.is_solved()));
+        for (i, a) in solutions.iter().enumerate() {
+            for b in solutions[i + 1..].iter() {
+                assert_ne!(
+                    a.board.as_slice(),
+                    b.board.as_slice(),
+                    "Solutions are unique"
+                );
+                assert_ne!(a.placements, b.placements);
+            }
+        }
+    }
+
+    #[test]
+    fn abort_search() {
+        let (board, pieces) = domino_puzzle();
+        let options = SolveOptions::default().with_limits(Limits::default().with_max_nodes(2));
+        let (result, stats) = solve_with_options(DumbSolver::default(), &board, &pieces, &options);
+        let Err(DumbFailure::Aborted(aborted)) = result else {
+            panic!("Search should be aborted");
+        };
+        assert_eq!((aborted.reason, aborted.nodes), (AbortReason::NodeLimit, 2));
+        assert_eq!(stats.num_checked_boards, 3, "Stats include the root board");
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let options = SolveOptions::default().with_limits(Limits::default().with_cancel(cancel));
+        let (result, _) = solve_with_options(DlxSolver, &board, &pieces, &options);
+        assert!(
+            matches!(result, Err(DlxFailure::Aborted(a)) if a.reason == AbortReason::Cancelled)
+        );
+
+        // Solutions found before the limit are still returned
+        let options = SolveOptions::default().with_limits(Limits::default().with_max_nodes(10));
+        let mut solutions = solve_all_with_options(DlxSolver, &board, &pieces, &options);
+        let count = solutions.count_remaining(None);
+        assert!(count > 0 && count < 18);
+        assert_eq!(solutions.aborted().unwrap().nodes, 10);
+        assert!(solve_all(DlxSolver, &board, &pieces).aborted().is_none());
+
+        // Counting tells that the count is partial
+        let (partial, aborted, _) =
+            count_solutions_with_options(DlxSolver, &board, &pieces, None, &options);
+        assert_eq!((partial, aborted.map(|a| a.nodes)), (count, Some(10)));
+        let (_, aborted, _) =
+            count_distinct_solutions_with_options(DlxSolver, &board, &pieces, None, &options);
+        assert!(aborted.is_some());
+        let (_, aborted, _) = count_solutions_with_options(
+            DlxSolver,
+            &board,
+            &pieces,
+            None,
+            &SolveOptions::default(),
+        );
+        assert!(aborted.is_none());
+    }
+
+    /// Keeps the pieces placed on the board and counts the events
+    #[derive(Default)]
+    struct Recorder {
+        placed: Mutex<Vec<u8>>,
+        events: Mutex<(usize, usize)>,
+    }
+
+    impl Observer for Recorder {
+        fn placed(&self, piece: &Piece, _position: Position, depth: usize) {
+            let mut placed = self.placed.lock().unwrap();
+            assert_eq!(placed.len(), depth);
+            placed.push(piece.id());
+            self.events.lock().unwrap().0 += 1;
+        }
+
+        fn backtracked(&self, depth: usize) {
+            let mut placed = self.placed.lock().unwrap();
+            assert_eq!(placed.len(), depth + 1);
+            placed.pop();
+        }
+
+        fn solution(&self, board: &Board) {
+            assert!(board.is_solved());
+            assert_eq!(self.placed.lock().unwrap().len(), 3);
+            self.events.lock().unwrap().1 += 1;
+        }
+    }
+
+    #[test]
+    fn observe_search() {
+        let (board, pieces) = domino_puzzle();
+        let recorder = Arc::new(Recorder::default());
+        let options = SolveOptions::default().with_observer(recorder.clone());
+
+        let mut solutions = solve_all_with_options(DlxSolver, &board, &pieces, &options);
+        assert_eq!(solutions.count_remaining(None), 18);
+        let stats = solutions.into_stats();
+        assert_eq!(*recorder.events.lock().unwrap(), (stats.nodes_visited, 18));
+        assert!(recorder.placed.lock().unwrap().is_empty());
+
+        let recorder = Arc::new(Recorder::default());
+        let options = SolveOptions::default().with_observer(recorder.clone());
+        let mut solutions =
+            solve_all_with_options(DumbSolver::default(), &board, &pieces, &options);
+        assert_eq!(solutions.count_remaining(None), 18);
+        let stats = solutions.into_stats();
+        let events = *recorder.events.lock().unwrap();
+        assert_eq!(events, (stats.num_checked_boards - 1, 18));
+        assert!(recorder.placed.lock().unwrap().is_empty());
+
+        // On a single thread the events of the tasks and their searches form one search tree
+        let recorder = Arc::new(Recorder::default());
+        let options = SolveOptions::default().with_observer(recorder.clone());
+        let solver = ParallelSolver::new(DlxSolver).with_threads(1);
+        let mut solutions = solve_all_with_options(solver, &board, &pieces, &options);
+        assert_eq!(solutions.count_remaining(None), 18);
+        let stats = solutions.into_stats();
+        let events = *recorder.events.lock().unwrap();
+        assert_eq!(events, (stats.tasks + stats.workers.nodes_visited, 18));
+        assert!(recorder.placed.lock().unwrap().is_empty());
+
+        let recorder = Arc::new(Recorder::default());
+        let options = SolveOptions::default().with_observer(recorder.clone());
+        let mut solutions =
+            solve_all_with_options(CellSolver::default(), &board, &pieces, &options);
+        assert_eq!(solutions.count_remaining(None), 18);
+        let stats = solutions.into_stats();
+        assert_eq!(*recorder.events.lock().unwrap(), (stats.placements, 18));
+        assert!(recorder.placed.lock().unwrap().is_empty());
+
+        // Every solver keeps the pieces of the solution placed, if the search is not continued
+        fn placed_after_solution<S: Solvable>(solver: S) -> usize {
+            let (board, pieces) = domino_puzzle();
+            let recorder = Arc::new(Recorder::default());
+            let options = SolveOptions::default().with_observer(recorder.clone());
+            let (result, _) = solve_with_options(solver, &board, &pieces, &options);
+            assert!(result.is_ok());
+            let placed = recorder.placed.lock().unwrap();
+            placed.len()
+        }
+        assert_eq!(placed_after_solution(DlxSolver), 3);
+        assert_eq!(placed_after_solution(DumbSolver::default()), 3);
+        assert_eq!(placed_after_solution(CellSolver::default()), 3);
+    }
+
+    /// Board with only the center 4x4 area not blocked
+    fn center_board() -> Board {
+        let mut board = Board::default();
+        for y in 0..8 {
+            for x in 0..8 {
+                if !(2..6).contains(&x) || !(2..6).contains(&y) {
+                    board.block(Position::new(x, y));
+                }
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn distinct_solutions() {
+        let board = center_board();
+        let pieces = vec![
+            piece(1, &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]),
+            piece(2, &[(0, 0), (1, 0), (0, 1), (1, 1)]),
+            piece(3, &[(0, 0), (1, 0), (2, 0), (3, 0)]),
+            piece(4, &[(0, 0), (1, 0), (2, 0)]),
+        ];
+        assert!(break_symmetry(&board, &pieces).is_some());
+
+        let mut canonical = HashSet::new();
+        let all = solve_all(DlxSolver, &board, &pieces).inspect(|s| {
+            canonical.insert(s.board.canonical());
+        });
+        assert_eq!(all.count(), 8 * canonical.len());
+        assert!(!canonical.is_empty());
+
+        let (distinct, _) = count_distinct_solutions(DlxSolver, &board, &pieces, None);
+        assert_eq!(distinct, canonical.len());
+        let (distinct, _) = count_distinct_solutions(DumbSolver::default(), &board, &pieces, None);
+        assert_eq!(distinct, canonical.len());
+    }
+
+    #[test]
+    fn distinct_one_sided_solutions() {
+        let board = center_board();
+        let one_sided = Piece::new(
+            1,
+            vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(0, 1),
+                Position::new(1, 1),
+                Position::new(0, 2),
+            ],
+        )
+        .with_policy(OrientationPolicy::RotationsOnly);
+        let pieces = vec![
+            one_sided.get_all_transforms(),
+            piece(2, &[(0, 0), (1, 0), (0, 1), (1, 1)]),
+            piece(3, &[(0, 0), (1, 0), (2, 0), (3, 0)]),
+            piece(4, &[(0, 0), (1, 0), (2, 0)]),
+        ];
+        // Mirroring a solution flips the piece over, so the variants are not solutions
+        assert!(break_symmetry(&board, &pieces).is_none());
+
+        let canonical = solve_all(DlxSolver, &board, &pieces)
+            .map(|s| s.board.canonical())
+            .collect::<HashSet<_>>();
+        let (all, _) = count_solutions(DlxSolver, &board, &pieces, None);
+        assert_eq!(all, 4 * canonical.len());
+        let (distinct, _) = count_distinct_solutions(DlxSolver, &board, &pieces, None);
+        assert_eq!(distinct, canonical.len());
+    }
+
+    #[test]
+    fn distinct_congruent_solutions() {
+        // The two dominoes in the 2x2 center can be placed in two ways, which are rotations of each other
+        let mut board = Board::default();
+        for y in 0..8 {
+            for x in 0..8 {
+                if !(3..5).contains(&x) || !(3..5).contains(&y) {
+                    board.block(Position::new(x, y));
+                }
+            }
+        }
+        let pieces = vec![piece(1, &[(0, 0), (1, 0)]), piece(2, &[(0, 0), (1, 0)])];
+
+        assert_eq!(count_solutions(DlxSolver, &board, &pieces, None).0, 4);
+        assert_eq!(
+            count_distinct_solutions(DlxSolver, &board, &pieces, None).0,
+            1
+        );
+    }
+
+    fn pentominoes() -> Vec<Vec<Piece>> {
+        vec![
+            piece(1, &[(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)]),
+            piece(2, &[(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]),
+            piece(3, &[(0, 0), (0, 1), (0, 2), (0, 3), (1, 3)]),
+            piece(4, &[(1, 0), (1, 1), (1, 2), (0, 2), (0, 3)]),
+            piece(5, &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]),
+            piece(6, &[(0, 0), (1, 0), (2, 0), (1, 1), (1, 2)]),
+            piece(7, &[(0, 0), (2, 0), (0, 1), (1, 1), (2, 1)]),
+            piece(8, &[(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)]),
+            piece(9, &[(0, 0), (0, 1), (1, 1), (1, 2), (2, 2)]),
+            piece(10, &[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)]),
+            piece(11, &[(1, 0), (0, 1), (1, 1), (1, 2), (1, 3)]),
+            piece(12, &[(0, 0), (1, 0), (1, 1), (1, 2), (2, 2)]),
+        ]
+    }
+
+    #[test]
+    fn pentomino_rectangle() {
+        // The 3x20 rectangle has 2 distinct solutions, each with 4 symmetric variants
+        let board = Board::new(20, 3);
+        let pieces = pentominoes();
+        assert_eq!(count_solutions(DlxSolver, &board, &pieces, None).0, 8);
+        assert_eq!(
+            count_distinct_solutions(DlxSolver, &board, &pieces, None).0,
+            2
+        );
+
+        let restricted = break_symmetry(&board, &pieces).unwrap();
+        let mut canonical = HashSet::new();
+        for solution in solve_all(DlxSolver, &board, &restricted) {
+            assert!(canonical.insert(solution.board.canonical()));
+        }
+        assert_eq!(canonical.len(), 2);
+    }
+
+    #[test]
+    fn pentomino_square_with_hole() {
+        let mut board = Board::default();
+        for (x, y) in [(3, 3), (4, 3), (3, 4), (4, 4)] {
+            board.block(Position::new(x, y));
+        }
+        let pieces = pentominoes();
+
+        let (result, _) = solve(DlxSolver, &board, &pieces);
+        let solution = result.ok().expect("Board with hole is solvable").board;
+        assert!(solution.is_solved());
+        assert_eq!(solution.get_value(Position::new(3, 3)), 0);
+        assert!(solution.is_blocked(Position::new(4, 4)));
+
+        // The classic puzzle has 65 distinct solutions
+        let (distinct, _) = count_distinct_solutions(DlxSolver, &board, &pieces, None);
+        assert_eq!(distinct, 65);
+    }
 }
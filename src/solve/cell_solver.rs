@@ -0,0 +1,447 @@
+use std::{fmt::Display, sync::Arc};
+
+use crate::{board::Board, piece::Piece, position::Position, solution::Solution};
+
+use super::{
+    build_solution,
+    limits::{Aborted, Budget},
+    observer::{explored, Observer, Progress, PROGRESS_INTERVAL},
+    parallel_solver::MergeStats,
+    transposition::{StateKey, TranspositionStats, TranspositionTable},
+    Enumerable, Search, Solvable, SolveOptions, SolveResult,
+};
+
+#[derive(Default)]
+pub struct CellStats {
+    /// Pieces placed on the board
+    pub placements: usize,
+    /// Placements that were checked with [Board::can_place_piece] to find the candidates of a cell
+    pub candidates_checked: usize,
+    /// Boards with an empty cell that no remaining piece can cover
+    pub dead_cells: usize,
+    /// Lookups of boards in the transposition table, if one is used
+    pub transpositions: TranspositionStats,
+}
+
+impl MergeStats for CellStats {
+    fn merge(&mut self, other: Self) {
+        self.placements += other.placements;
+        self.candidates_checked += other.candidates_checked;
+        self.dead_cells += other.dead_cells;
+        self.transpositions.merge(other.transpositions);
+    }
+}
+
+impl Display for CellStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Placed pieces: {}", self.placements)?;
+        writeln!(f, "Checked candidates: {}", self.candidates_checked)?;
+        writeln!(f, "Dead cells: {}", self.dead_cells)?;
+        if self.transpositions != TranspositionStats::default() {
+            write!(f, "{}", self.transpositions)?;
+        }
+        Ok(())
+    }
+}
+
+pub enum CellFailure {
+    NoMorePieces,
+    NotSolvable,
+    Aborted(Aborted),
+}
+
+impl Display for CellFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CellFailure::NoMorePieces => writeln!(f, "No more pieces"),
+            CellFailure::NotSolvable => writeln!(f, "Not Solvable"),
+            CellFailure::Aborted(aborted) => writeln!(f, "{aborted}"),
+        }
+    }
+}
+
+/// Which empty cell is filled next
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CellChoice {
+    /// The first empty cell, from the top left
+    First,
+    /// The empty cell that the fewest placements of the remaining pieces can cover
+    #[default]
+    FewestCandidates,
+}
+
+/// Fills one empty cell after the other, trying every remaining piece and transform that covers it.
+///
+/// Unlike the [DumbSolver](super::dumb_solver::DumbSolver) the order of the pieces does not matter,
+/// and a cell that no piece can cover ends the branch right away.
+/// The copies of a piece are one candidate, as long as some of them are left.
+#[derive(Clone, Copy, Default)]
+pub struct CellSolver {
+    pub choice: CellChoice,
+    /// Capacity of the [TranspositionTable] that skips boards which were a dead end before.
+    /// No table is used without it
+    pub transpositions: Option<usize>,
+}
+
+impl CellSolver {
+    pub fn new(choice: CellChoice) -> Self {
+        Self {
+            choice,
+            transpositions: None,
+        }
+    }
+
+    pub fn with_transpositions(mut self, capacity: usize) -> Self {
+        self.transpositions = Some(capacity);
+        self
+    }
+}
+
+impl Solvable for CellSolver {
+    type SolveStats = CellStats;
+    type Failure = CellFailure;
+
+    fn solve(
+        &self,
+        stats: &mut Self::SolveStats,
+        board: &Board,
+        pieces: &[Vec<Piece>],
+        options: &SolveOptions,
+    ) -> SolveResult<Self::Failure> {
+        if pieces.is_empty() {
+            return Err(CellFailure::NoMorePieces);
+        }
+
+        let mut search = self.search(stats, board, pieces, options);
+        search.next_solution(stats).ok_or_else(|| {
+            search
+                .aborted()
+                .map_or(CellFailure::NotSolvable, CellFailure::Aborted)
+        })
+    }
+}
+
+impl Enumerable for CellSolver {
+    type Search<'a> = CellSearch<'a>;
+
+    fn search<'a>(
+        &self,
+        stats: &mut Self::SolveStats,
+        board: &Board,
+        pieces: &'a [Vec<Piece>],
+        options: &SolveOptions,
+    ) -> Self::Search<'a> {
+        let copies = pieces
+            .iter()
+            .map(|t| t.first().map_or(0, Piece::count))
+            .collect::<Vec<_>>();
+        let total = copies.iter().sum();
+        let mut search = CellSearch {
+            choice: self.choice,
+            pieces,
+            copies,
+            total,
+            used: vec![0; pieces.len()],
+            stack: Vec::new(),
+            // The state of the search only fits into the key with at most 64 copies
+            table: self
+                .transpositions
+                .filter(|_| total <= 64)
+                .map(TranspositionTable::new),
+            budget: Budget::new(&options.limits),
+            observer: options.observer.clone(),
+        };
+        // A piece without transforms can not be placed, so there is nothing to search
+        if !pieces.is_empty() && pieces.iter().all(|t| !t.is_empty()) {
+            search.push(stats, *board);
+        }
+        search
+    }
+}
+
+/// A placement of a transform of a piece with its top left corner at the position
+#[derive(Clone, Copy)]
+struct Candidate {
+    piece: usize,
+    transform: usize,
+    position: Position,
+}
+
+/// A board of the search with the placements covering its chosen cell
+struct Frame {
+    board: Board,
+    candidates: Vec<Candidate>,
+    /// Index of the next candidate to try. The candidate before it is placed right now
+    cursor: usize,
+    /// True if a solution was found after the board
+    solved: bool,
+}
+
+pub struct CellSearch<'a> {
+    choice: CellChoice,
+    pieces: &'a [Vec<Piece>],
+    /// Number of copies of every piece
+    copies: Vec<usize>,
+    /// Number of copies in all pieces together
+    total: usize,
+    /// Copies of every piece placed by the frames on the stack
+    used: Vec<usize>,
+    stack: Vec<Frame>,
+    table: Option<TranspositionTable>,
+    budget: Budget,
+    observer: Option<Arc<dyn Observer + Send + Sync>>,
+}
+
+impl CellSearch<'_> {
+    fn notify(&self, event: impl FnOnce(&dyn Observer)) {
+        if let Some(observer) = &self.observer {
+            event(observer.as_ref());
+        }
+    }
+
+    fn progress(&self) -> Progress {
+        let levels = self
+            .stack
+            .iter()
+            .map(|frame| (frame.cursor.saturating_sub(1), frame.candidates.len()));
+        Progress {
+            nodes: self.budget.nodes(),
+            explored: explored(levels),
+        }
+    }
+
+    /// Gets the state of the search on the board, with the copies that are not used.
+    ///
+    /// The copies of a piece are used in order, so each copy is one bit
+    fn key(&self, board: &Board) -> StateKey {
+        let mut remaining = 0u64;
+        let mut bit = 0;
+        for (copies, used) in self.copies.iter().zip(self.used.iter()) {
+            for copy in *used..*copies {
+                remaining |= 1 << (bit + copy);
+            }
+            bit += copies;
+        }
+        StateKey::new(board, remaining)
+    }
+
+    /// Collects the candidate placed by every frame, which is the one before its cursor
+    fn solution(&self) -> Solution {
+        let placed = self
+            .stack
+            .iter()
+            .map(|frame| {
+                let candidate = frame.candidates[frame.cursor - 1];
+                (candidate.piece, candidate.transform, candidate.position)
+            })
+            .collect();
+        build_solution(&self.stack[0].board, self.pieces, placed)
+    }
+
+    /// Finds every placement of a remaining piece that covers the cell
+    fn candidates(&self, stats: &mut CellStats, board: &Board, cell: Position) -> Vec<Candidate> {
+        let mut candidates = Vec::new();
+        for (piece, all_transforms) in self.pieces.iter().enumerate() {
+            if self.used[piece] == self.copies[piece] {
+                continue;
+            }
+            for (transform, shape) in all_transforms.iter().enumerate() {
+                // Every point of the piece can be the one on the cell
+                for point in shape.points() {
+                    let (Some(x), Some(y)) = (
+                        cell.x().checked_sub(point.x()),
+                        cell.y().checked_sub(point.y()),
+                    ) else {
+                        continue;
+                    };
+                    let position = Position::new(x, y);
+                    stats.candidates_checked += 1;
+                    if board.can_place_piece(position, shape) {
+                        candidates.push(Candidate {
+                            piece,
+                            transform,
+                            position,
+                        });
+                    }
+                }
+            }
+        }
+        candidates
+    }
+
+    /// Chooses the cell to fill next and pushes its candidates
+    fn push(&mut self, stats: &mut CellStats, board: Board) {
+        let mut best: Option<Vec<Candidate>> = None;
+        for cell in board.positions().filter(|p| board.is_free(*p)) {
+            let candidates = self.candidates(stats, &board, cell);
+            if best
+                .as_ref()
+                .is_none_or(|best| candidates.len() < best.len())
+            {
+                // No cell can have fewer candidates than none or one
+                let done = self.choice == CellChoice::First || candidates.len() <= 1;
+                best = Some(candidates);
+                if done {
+                    break;
+                }
+            }
+        }
+        let candidates = best.unwrap_or_default();
+        if candidates.is_empty() {
+            stats.dead_cells += 1;
+        }
+        self.stack.push(Frame {
+            board,
+            candidates,
+            cursor: 0,
+            solved: false,
+        });
+    }
+}
+
+impl Search for CellSearch<'_> {
+    type Stats = CellStats;
+
+    fn next_solution(&mut self, stats: &mut Self::Stats) -> Option<Solution> {
+        while let Some(depth) = self.stack.len().checked_sub(1) {
+            let frame = &mut self.stack[depth];
+            // Take back the candidate that was placed by this frame before
+            if let Some(previous) = frame.cursor.checked_sub(1) {
+                let piece = frame.candidates[previous].piece;
+                self.used[piece] -= 1;
+                self.notify(|o| o.backtracked(depth));
+            }
+
+            let frame = &mut self.stack[depth];
+            let Some(&candidate) = frame.candidates.get(frame.cursor) else {
+                // Every candidate was tried, so the board is a dead end if none lead to a solution
+                let frame = self.stack.pop().unwrap();
+                if !frame.solved && self.table.is_some() {
+                    let key = self.key(&frame.board);
+                    if let Some(table) = &mut self.table {
+                        table.mark_dead(key, &mut stats.transpositions);
+                    }
+                }
+                continue;
+            };
+            frame.cursor += 1;
+            let mut board = frame.board;
+
+            if !self.budget.spend() {
+                return None;
+            }
+            if self.budget.nodes().is_multiple_of(PROGRESS_INTERVAL) {
+                self.notify(|o| o.progress(self.progress()));
+            }
+
+            let Candidate {
+                piece,
+                transform,
+                position,
+            } = candidate;
+            let shape = &self.pieces[piece][transform];
+            // The copies are labeled again in the solution, so any id does
+            let placed = board.place_piece(position, shape);
+            debug_assert!(placed, "Candidates only hold placements that fit");
+            self.used[piece] += 1;
+            stats.placements += 1;
+            self.notify(|o| o.placed(shape, position, depth));
+
+            // The next round takes the piece back, if the board is not pushed
+            match (board.is_solved(), depth + 1 == self.total) {
+                (true, true) => {
+                    for frame in self.stack.iter_mut() {
+                        frame.solved = true;
+                    }
+                    self.notify(|o| o.solution(&board));
+                    return Some(self.solution());
+                }
+                (true, false) | (false, true) => continue,
+                (false, false) => {
+                    if let Some(table) = &self.table {
+                        if table.is_dead(&self.key(&board), &mut stats.transpositions) {
+                            continue;
+                        }
+                    }
+                    self.push(stats, board)
+                }
+            }
+        }
+        None
+    }
+
+    fn aborted(&self) -> Option<Aborted> {
+        self.budget.aborted()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::Board,
+        get_game_pieces,
+        piece::Piece,
+        position::Position,
+        solve::{count_solutions, dlx_solver::DlxSolver, solve},
+    };
+
+    use super::{CellChoice, CellSolver};
+
+    #[test]
+    fn solves_game_pieces() {
+        let pieces = get_game_pieces()
+            .iter()
+            .map(Piece::get_all_transforms)
+            .collect::<Vec<_>>();
+        let (result, stats) = solve(CellSolver::default(), &Board::default(), &pieces);
+        let solution = result.ok().expect("Game pieces should be solvable");
+        let board = solution.board;
+        assert!(board.is_solved());
+        assert_eq!(solution.placements.len(), pieces.len());
+        for all_transforms in pieces.iter() {
+            let piece = &all_transforms[0];
+            let cells = board.as_slice().iter().filter(|v| **v == piece.id());
+            assert_eq!(cells.count(), piece.points().len(), "Piece used once");
+        }
+        assert!(stats.placements >= pieces.len());
+    }
+
+    #[test]
+    fn counts_like_dlx() {
+        let board = Board::new(5, 4);
+        let pieces = [
+            &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)][..],
+            &[(0, 0), (1, 0), (2, 0), (3, 0), (0, 1)],
+            &[(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)],
+            &[(0, 0), (1, 0), (1, 1), (2, 1), (3, 1)],
+        ]
+        .iter()
+        .zip(1..)
+        .map(|(points, id)| {
+            let points = points.iter().map(|(x, y)| Position::new(*x, *y)).collect();
+            Piece::new(id, points).get_all_transforms()
+        })
+        .collect::<Vec<_>>();
+        let (expected, _) = count_solutions(DlxSolver, &board, &pieces, None);
+        assert!(expected > 0);
+
+        let (count, fewest) = count_solutions(CellSolver::default(), &board, &pieces, None);
+        assert_eq!(count, expected);
+        let solver = CellSolver::new(CellChoice::First);
+        let (count, first) = count_solutions(solver, &board, &pieces, None);
+        assert_eq!(count, expected);
+        assert!(fewest.placements <= first.placements);
+
+        let solver = CellSolver::new(CellChoice::First).with_transpositions(1000);
+        let (count, table) = count_solutions(solver, &board, &pieces, None);
+        assert_eq!(count, expected);
+        assert!(table.transpositions.hits > 0);
+        assert!(table.placements < first.placements);
+
+        // A small table drops dead boards, but still finds every solution
+        let solver = CellSolver::new(CellChoice::First).with_transpositions(5);
+        let (count, small) = count_solutions(solver, &board, &pieces, None);
+        assert_eq!(count, expected);
+        assert!(small.transpositions.evictions > 0);
+    }
+}
@@ -0,0 +1,530 @@
+use std::{fmt::Display, sync::Arc};
+
+use crate::{
+    board::{Board, MAX_FIELDS},
+    piece::Piece,
+    solution::Solution,
+    Position,
+};
+
+use super::{
+    build_solution,
+    limits::{Aborted, Budget},
+    observer::{explored, Observer, Progress, PROGRESS_INTERVAL},
+    parallel_solver::MergeStats,
+    Enumerable, Search, Solvable, SolveOptions, SolveResult,
+};
+
+#[derive(Default)]
+pub struct DlxStats {
+    pub rows_generated: usize,
+    pub nodes_visited: usize,
+    pub backtracks: usize,
+}
+
+impl MergeStats for DlxStats {
+    fn merge(&mut self, other: Self) {
+        self.rows_generated += other.rows_generated;
+        self.nodes_visited += other.nodes_visited;
+        self.backtracks += other.backtracks;
+    }
+}
+
+impl Display for DlxStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Generated rows: {}", self.rows_generated)?;
+        writeln!(f, "Visited nodes: {}", self.nodes_visited)?;
+        writeln!(f, "Backtracks: {}", self.backtracks)
+    }
+}
+
+pub enum DlxFailure {
+    NoMorePieces,
+    NotSolvable,
+    Aborted(Aborted),
+}
+
+impl Display for DlxFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DlxFailure::NoMorePieces => writeln!(f, "No more pieces"),
+            DlxFailure::NotSolvable => writeln!(f, "Not Solvable"),
+            DlxFailure::Aborted(aborted) => writeln!(f, "{aborted}"),
+        }
+    }
+}
+
+/// Solves the board as an exact cover problem with Knuth's Dancing Links.
+///
+/// Every empty cell of the board and every piece is a column.
+/// Every legal placement of a transform of a piece is a row, covering the cells
+/// it occupies and the column of its piece. So every piece has to be used exactly once.
+///
+/// The column of a piece with several copies is a counter, that has to be covered once for every copy.
+/// It is only removed after the last copy is placed and never chosen while more than one copy is left,
+/// so the same copies are not tried in every order.
+#[derive(Clone, Copy)]
+pub struct DlxSolver;
+
+impl Solvable for DlxSolver {
+    type SolveStats = DlxStats;
+    type Failure = DlxFailure;
+
+    fn solve(
+        &self,
+        stats: &mut Self::SolveStats,
+        board: &Board,
+        pieces: &[Vec<Piece>],
+        options: &SolveOptions,
+    ) -> SolveResult<Self::Failure> {
+        if pieces.is_empty() {
+            return Err(DlxFailure::NoMorePieces);
+        }
+
+        let mut search = self.search(stats, board, pieces, options);
+        search.next_solution(stats).ok_or_else(|| {
+            search
+                .aborted()
+                .map_or(DlxFailure::NotSolvable, DlxFailure::Aborted)
+        })
+    }
+}
+
+impl Enumerable for DlxSolver {
+    type Search<'a> = DlxSearch<'a>;
+
+    fn search<'a>(
+        &self,
+        stats: &mut Self::SolveStats,
+        board: &Board,
+        pieces: &'a [Vec<Piece>],
+        options: &SolveOptions,
+    ) -> Self::Search<'a> {
+        let search = DlxSearch::new(board, pieces, options);
+        stats.rows_generated += search.rows.len();
+        search
+    }
+}
+
+/// A placement of a piece transform on the board. Every row of the matrix is one placement
+struct Row {
+    piece: usize,
+    transform: usize,
+    position: Position,
+}
+
+/// A node in the dancing links matrix.
+///
+/// The first nodes are the column headers, starting with the root header at index 0.
+#[derive(Clone, Copy)]
+struct Node {
+    left: usize,
+    right: usize,
+    up: usize,
+    down: usize,
+    column: usize,
+    row: usize,
+}
+
+/// The root header of the matrix
+const ROOT: usize = 0;
+
+/// Where the search continues when calling [DlxSearch::next_solution]
+#[derive(Clone, Copy)]
+enum Resume {
+    /// Start a new level
+    Enter,
+    /// Try the next row at the current level
+    Next,
+    /// The search is exhausted
+    Done,
+}
+
+/// Resumable Algorithm X search over a dancing links matrix.
+///
+/// The search is implemented without recursion, following the levels
+/// of Algorithm X in The Art of Computer Programming 7.2.2.1.
+pub struct DlxSearch<'a> {
+    board: Board,
+    pieces: &'a [Vec<Piece>],
+    nodes: Vec<Node>,
+    sizes: Vec<usize>,
+    /// How often each column still has to be covered, which is more than once for pieces with copies
+    counts: Vec<usize>,
+    rows: Vec<Row>,
+    /// The node of the row chosen at each level
+    chosen: Vec<usize>,
+    resume: Resume,
+    budget: Budget,
+    observer: Option<Arc<dyn Observer + Send + Sync>>,
+}
+
+impl<'a> DlxSearch<'a> {
+    fn new(board: &Board, pieces: &'a [Vec<Piece>], options: &SolveOptions) -> Self {
+        // One column for every empty cell, followed by one column for every piece
+        let mut cell_columns = [0usize; MAX_FIELDS];
+        let mut num_columns = 0;
+        for (index, position) in board.positions().enumerate() {
+            if board.is_free(position) {
+                num_columns += 1;
+                cell_columns[index] = num_columns;
+            }
+        }
+        let first_piece_column = num_columns + 1;
+        num_columns += pieces.len();
+
+        let mut search = Self {
+            board: *board,
+            pieces,
+            nodes: Vec::new(),
+            sizes: vec![0; num_columns + 1],
+            counts: vec![1; num_columns + 1],
+            rows: Vec::new(),
+            chosen: Vec::new(),
+            resume: Resume::Enter,
+            budget: Budget::new(&options.limits),
+            observer: options.observer.clone(),
+        };
+
+        for column in 0..=num_columns {
+            search.nodes.push(Node {
+                left: if column == 0 { num_columns } else { column - 1 },
+                right: if column == num_columns { 0 } else { column + 1 },
+                up: column,
+                down: column,
+                column,
+                row: usize::MAX,
+            });
+        }
+
+        for (piece_index, transforms) in pieces.iter().enumerate() {
+            // A piece without transforms still has to be placed once, but has no rows to cover it
+            search.counts[first_piece_column + piece_index] =
+                transforms.first().map_or(1, Piece::count);
+            for (transform, piece) in transforms.iter().enumerate() {
+                for position in board.positions() {
+                    if !board.can_place_piece(position, piece) {
+                        continue;
+                    }
+                    let mut columns = piece
+                        .points()
+                        .iter()
+                        .map(|point| {
+                            // We can unwrap, because we check that in the can_place_piece function
+                            let p = position.try_add(point).unwrap();
+                            cell_columns[board.index(p).unwrap()]
+                        })
+                        .collect::<Vec<_>>();
+                    columns.push(first_piece_column + piece_index);
+                    search.add_row(
+                        Row {
+                            piece: piece_index,
+                            transform,
+                            position,
+                        },
+                        &columns,
+                    );
+                }
+            }
+        }
+
+        search
+    }
+
+    /// Appends a row covering the given columns to the bottom of the matrix
+    fn add_row(&mut self, row: Row, columns: &[usize]) {
+        let row_index = self.rows.len();
+        self.rows.push(row);
+
+        let first = self.nodes.len();
+        let last = first + columns.len() - 1;
+        for &column in columns.iter() {
+            let index = self.nodes.len();
+            let up = self.nodes[column].up;
+            self.nodes.push(Node {
+                left: if index == first { last } else { index - 1 },
+                right: if index == last { first } else { index + 1 },
+                up,
+                down: column,
+                column,
+                row: row_index,
+            });
+            self.nodes[up].down = index;
+            self.nodes[column].up = index;
+            self.sizes[column] += 1;
+        }
+    }
+
+    /// Removes the column from the header list and all rows of the column from the other columns
+    fn cover(&mut self, column: usize) {
+        let Node { left, right, .. } = self.nodes[column];
+        self.nodes[left].right = right;
+        self.nodes[right].left = left;
+
+        let mut i = self.nodes[column].down;
+        while i != column {
+            let mut j = self.nodes[i].right;
+            while j != i {
+                let Node { up, down, .. } = self.nodes[j];
+                self.nodes[up].down = down;
+                self.nodes[down].up = up;
+                self.sizes[self.nodes[j].column] -= 1;
+                j = self.nodes[j].right;
+            }
+            i = self.nodes[i].down;
+        }
+    }
+
+    /// Reverts [DlxSearch::cover] for the column
+    fn uncover(&mut self, column: usize) {
+        let mut i = self.nodes[column].up;
+        while i != column {
+            let mut j = self.nodes[i].left;
+            while j != i {
+                self.sizes[self.nodes[j].column] += 1;
+                let Node { up, down, .. } = self.nodes[j];
+                self.nodes[up].down = j;
+                self.nodes[down].up = j;
+                j = self.nodes[j].left;
+            }
+            i = self.nodes[i].up;
+        }
+
+        let Node { left, right, .. } = self.nodes[column];
+        self.nodes[left].right = column;
+        self.nodes[right].left = column;
+    }
+
+    /// Covers all other columns of the row of the node.
+    /// A counter column is only covered once it has no copies left
+    fn cover_row(&mut self, node: usize) {
+        let mut j = self.nodes[node].right;
+        while j != node {
+            let column = self.nodes[j].column;
+            self.counts[column] -= 1;
+            if self.counts[column] == 0 {
+                self.cover(column);
+            }
+            j = self.nodes[j].right;
+        }
+    }
+
+    /// Reverts [DlxSearch::cover_row] for the row of the node
+    fn uncover_row(&mut self, node: usize) {
+        let mut j = self.nodes[node].left;
+        while j != node {
+            let column = self.nodes[j].column;
+            if self.counts[column] == 0 {
+                self.uncover(column);
+            }
+            self.counts[column] += 1;
+            j = self.nodes[j].left;
+        }
+    }
+
+    /// Chooses the column with the fewest rows left, that has to be covered only once more.
+    ///
+    /// Returns [None] if there is no such column, or a counter column has fewer rows than copies left
+    fn choose_column(&self) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        let mut c = self.nodes[ROOT].right;
+        while c != ROOT {
+            if self.sizes[c] < self.counts[c] {
+                return None;
+            }
+            if self.counts[c] == 1 && best.is_none_or(|best| self.sizes[c] < self.sizes[best]) {
+                best = Some(c);
+            }
+            c = self.nodes[c].right;
+        }
+        best
+    }
+
+    /// Runs the search until the next solution is found.
+    ///
+    /// The rows of the solution are the rows of the chosen nodes.
+    /// Returns false if there are no more solutions or the budget is used up.
+    fn advance(&mut self, stats: &mut DlxStats) -> bool {
+        loop {
+            match self.resume {
+                Resume::Done => return false,
+                Resume::Enter if !self.budget.spend() => return false,
+                Resume::Enter => {
+                    if self.nodes[ROOT].right == ROOT {
+                        // All columns are covered, so we found a solution
+                        self.resume = Resume::Next;
+                        return true;
+                    }
+                    if self.budget.nodes().is_multiple_of(PROGRESS_INTERVAL) {
+                        self.notify(|o| o.progress(self.progress()));
+                    }
+                    let Some(column) = self.choose_column() else {
+                        // No row can complete the remaining columns, so backtrack the current level
+                        self.resume = Resume::Next;
+                        continue;
+                    };
+                    self.cover(column);
+                    let node = self.nodes[column].down;
+                    self.chosen.push(node);
+                    self.try_node(stats);
+                }
+                Resume::Next => {
+                    // Backtrack the row of the current level and try the next one
+                    let Some(node) = self.chosen.pop() else {
+                        self.resume = Resume::Done;
+                        return false;
+                    };
+                    if node != self.nodes[node].column {
+                        stats.backtracks += 1;
+                        self.notify(|o| o.backtracked(self.chosen.len()));
+                        self.uncover_row(node);
+                        self.chosen.push(self.nodes[node].down);
+                        self.try_node(stats);
+                    } else {
+                        self.uncover(node);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Selects the row of the node at the top of the chosen stack.
+    ///
+    /// If the node is the column header, all rows of the column were tried.
+    fn try_node(&mut self, stats: &mut DlxStats) {
+        let node = *self.chosen.last().unwrap();
+        if node == self.nodes[node].column {
+            self.resume = Resume::Next;
+        } else {
+            stats.nodes_visited += 1;
+            self.notify(|o| {
+                let Row {
+                    piece,
+                    transform,
+                    position,
+                } = self.rows[self.nodes[node].row];
+                o.placed(
+                    &self.pieces[piece][transform],
+                    position,
+                    self.chosen.len() - 1,
+                );
+            });
+            self.cover_row(node);
+            self.resume = Resume::Enter;
+        }
+    }
+
+    fn notify(&self, event: impl FnOnce(&dyn Observer)) {
+        if let Some(observer) = &self.observer {
+            event(observer.as_ref());
+        }
+    }
+
+    /// Estimates the explored part from the position of the chosen rows in their columns
+    fn progress(&self) -> Progress {
+        let levels = self.chosen.iter().map(|&node| {
+            let column = self.nodes[node].column;
+            let mut index = 0;
+            let mut i = self.nodes[column].down;
+            while i != node && i != column {
+                index += 1;
+                i = self.nodes[i].down;
+            }
+            (index, self.sizes[column])
+        });
+        Progress {
+            nodes: self.budget.nodes(),
+            explored: explored(levels),
+        }
+    }
+
+    /// Builds the solution from the piece transforms of the chosen rows
+    fn build_solution(&self) -> Solution {
+        let placed = self
+            .chosen
+            .iter()
+            .map(|node| {
+                let Row {
+                    piece,
+                    transform,
+                    position,
+                } = self.rows[self.nodes[*node].row];
+                (piece, transform, position)
+            })
+            .collect();
+        build_solution(&self.board, self.pieces, placed)
+    }
+}
+
+impl Search for DlxSearch<'_> {
+    type Stats = DlxStats;
+
+    fn next_solution(&mut self, stats: &mut Self::Stats) -> Option<Solution> {
+        if !self.advance(stats) {
+            return None;
+        }
+        let solution = self.build_solution();
+        self.notify(|o| o.solution(&solution.board));
+        Some(solution)
+    }
+
+    fn skip_solution(&mut self, stats: &mut Self::Stats) -> bool {
+        if self.observer.is_some() {
+            // The observer needs the board of the solution
+            return self.next_solution(stats).is_some();
+        }
+        self.advance(stats)
+    }
+
+    fn aborted(&self) -> Option<Aborted> {
+        self.budget.aborted()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Board, get_game_pieces, piece::Piece, position::Position, solve::solve};
+
+    use super::DlxSolver;
+
+    #[test]
+    fn solves_game_pieces() {
+        let game_pieces = get_game_pieces();
+        let pieces = game_pieces
+            .iter()
+            .map(|p| p.get_all_transforms())
+            .collect::<Vec<_>>();
+
+        let (result, stats) = solve(DlxSolver, &Board::default(), &pieces);
+        let Ok(solution) = result else {
+            panic!("Game pieces should be solvable");
+        };
+        let board = solution.board;
+        assert!(board.is_solved(), "Board not solved");
+        for piece in game_pieces.iter() {
+            let cells = board.as_slice().iter().filter(|v| **v == piece.id());
+            assert_eq!(cells.count(), piece.points().len(), "Piece used once");
+        }
+        assert!(stats.rows_generated > 0);
+        assert!(stats.nodes_visited >= game_pieces.len());
+    }
+
+    #[test]
+    fn not_solvable() {
+        // A single 2x2 square can not fill the board
+        let pieces = vec![Piece::new(
+            1,
+            vec![
+                Position::new(0, 0),
+                Position::new(0, 1),
+                Position::new(1, 0),
+                Position::new(1, 1),
+            ],
+        )
+        .get_all_transforms()];
+
+        let (result, stats) = solve(DlxSolver, &Board::default(), &pieces);
+        assert!(result.is_err());
+        assert_eq!(stats.rows_generated, 49);
+    }
+}
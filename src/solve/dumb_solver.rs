@@ -1,38 +1,130 @@
-use std::fmt::Display;
+use std::{fmt::Display, sync::Arc};
 
 use crate::{
     board::Board,
-    board_tree::{BoardID, BoardTree},
+    board_tree::{BoardID, BoardTree, DeadEnd, Step},
     piece::Piece,
+    solution::Solution,
     Position,
 };
 
-use super::{Solvable, SolveResult};
+use super::{
+    build_solution,
+    limits::{Aborted, Budget},
+    observer::{explored, Observer, Progress, PROGRESS_INTERVAL},
+    parallel_solver::MergeStats,
+    placement_masks::PlacementMasks,
+    transposition::{StateKey, TranspositionStats, TranspositionTable},
+    Enumerable, Search, Solvable, SolveOptions,
+};
+
+/// How much of the search is recorded in the [DumbStats]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Trace {
+    /// Nothing, the counters stay at zero
+    Off,
+    /// Only the counters
+    #[default]
+    Counters,
+    /// The counters and a [BoardTree] of at most this many of the most recent boards
+    Ring(usize),
+    /// The counters and a [BoardTree] of every board. It can grow very large
+    Full,
+}
 
 #[derive(Default)]
 pub struct DumbStats {
+    trace: Trace,
     tree: BoardTree,
     pub num_checked_boards: usize,
     pub num_skiped_single: usize,
+    /// Boards with an empty region that the remaining pieces can not fill
+    pub num_pruned_regions: usize,
+    /// Lookups of boards in the transposition table, if one is used
+    pub transpositions: TranspositionStats,
 }
 
 impl DumbStats {
-    pub fn insert_board(&mut self, parent: Option<BoardID>, board: Board) -> BoardID {
-        self.num_checked_boards += 1;
-        self.tree.insert(board, parent)
+    /// Sets how much of the following searches is recorded
+    pub fn set_trace(&mut self, trace: Trace) {
+        self.trace = trace;
+        if let Trace::Ring(capacity) = trace {
+            if self.tree.capacity() != Some(capacity) && self.tree.is_empty() {
+                self.tree = BoardTree::with_capacity(capacity);
+            }
+        }
+    }
+
+    pub fn trace(&self) -> Trace {
+        self.trace
+    }
+
+    /// Records the board, depending on the [Trace]. Returns its id, if it was added to the tree
+    pub fn insert_board(
+        &mut self,
+        parent: Option<(BoardID, Step)>,
+        board: Board,
+    ) -> Option<BoardID> {
+        match self.trace {
+            Trace::Off => None,
+            Trace::Counters => {
+                self.num_checked_boards += 1;
+                None
+            }
+            Trace::Ring(_) | Trace::Full => {
+                self.num_checked_boards += 1;
+                Some(self.tree.insert(board, parent))
+            }
+        }
+    }
+
+    /// Records why the search stopped at the board with the id from [DumbStats::insert_board]
+    pub fn mark_dead_end(&mut self, node: Option<BoardID>, reason: DeadEnd) {
+        if self.trace == Trace::Off {
+            return;
+        }
+        match reason {
+            DeadEnd::SingleCell => self.num_skiped_single += 1,
+            DeadEnd::Region => self.num_pruned_regions += 1,
+            DeadEnd::NoPiecesLeft | DeadEnd::NoPlacement => {}
+        }
+        if let Some(node) = node {
+            self.tree.mark_dead_end(node, reason);
+        }
+    }
+
+    /// Gets the boards the search checked. It is empty unless [Trace::Ring] or [Trace::Full] is used
+    pub fn tree(&self) -> &BoardTree {
+        &self.tree
+    }
+}
+
+impl MergeStats for DumbStats {
+    fn merge(&mut self, other: Self) {
+        self.tree.merge(other.tree);
+        self.num_checked_boards += other.num_checked_boards;
+        self.num_skiped_single += other.num_skiped_single;
+        self.num_pruned_regions += other.num_pruned_regions;
+        self.transpositions.merge(other.transpositions);
     }
 }
 
 impl Display for DumbStats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Checked boards: {}", self.num_checked_boards)?;
-        writeln!(f, "Skipped single fields: {}", self.num_skiped_single)
+        writeln!(f, "Skipped single fields: {}", self.num_skiped_single)?;
+        writeln!(f, "Pruned regions: {}", self.num_pruned_regions)?;
+        if self.transpositions != TranspositionStats::default() {
+            write!(f, "{}", self.transpositions)?;
+        }
+        Ok(())
     }
 }
 
 pub enum DumbFailure {
     NoMorePieces,
     NotSolvable,
+    Aborted(Aborted),
 }
 
 impl Display for DumbFailure {
@@ -40,11 +132,36 @@ impl Display for DumbFailure {
         match self {
             DumbFailure::NoMorePieces => writeln!(f, "No more pieces"),
             DumbFailure::NotSolvable => writeln!(f, "Not Solvable"),
+            DumbFailure::Aborted(aborted) => writeln!(f, "{aborted}"),
         }
     }
 }
 
-pub struct DumbSolver;
+/// Places the pieces one after the other, in the order they are given, on every position that fits.
+///
+/// The copies of a piece are placed one after the other, each one after the position and transform of the copy before,
+/// so the same copies are not tried in every order
+#[derive(Clone, Copy, Default)]
+pub struct DumbSolver {
+    pub trace: Trace,
+    /// Capacity of the [TranspositionTable] that skips boards which were a dead end before.
+    /// No table is used without it
+    pub transpositions: Option<usize>,
+}
+
+impl DumbSolver {
+    pub fn new(trace: Trace) -> Self {
+        Self {
+            trace,
+            transpositions: None,
+        }
+    }
+
+    pub fn with_transpositions(mut self, capacity: usize) -> Self {
+        self.transpositions = Some(capacity);
+        self
+    }
+}
 
 impl Solvable for DumbSolver {
     type SolveStats = DumbStats;
@@ -55,76 +172,584 @@ impl Solvable for DumbSolver {
         stats: &mut Self::SolveStats,
         board: &crate::board::Board,
         pieces: &[Vec<crate::piece::Piece>],
+        options: &SolveOptions,
     ) -> super::SolveResult<Self::Failure> {
+        let mut search = self.search(stats, board, pieces, options);
+        if pieces.is_empty() {
+            return Err(DumbFailure::NoMorePieces);
+        }
+        search.next_solution(stats).ok_or_else(|| {
+            search
+                .aborted()
+                .map_or(DumbFailure::NotSolvable, DumbFailure::Aborted)
+        })
+    }
+}
+
+impl Enumerable for DumbSolver {
+    type Search<'a> = DumbSearch<'a>;
+
+    fn search<'a>(
+        &self,
+        stats: &mut Self::SolveStats,
+        board: &Board,
+        pieces: &'a [Vec<Piece>],
+        options: &SolveOptions,
+    ) -> Self::Search<'a> {
+        stats.set_trace(self.trace);
         let root = stats.insert_board(None, *board);
-        step(stats, board, pieces, 0, root)
+        let mut stack = Vec::new();
+        // A piece without transforms can not be placed, so there is nothing to search
+        if !pieces.is_empty() && pieces.iter().all(|t| !t.is_empty()) {
+            stack.push(Frame {
+                board: *board,
+                node: root,
+                cursor: 0,
+                solved: false,
+            });
+        }
+        let slots = copy_slots(pieces);
+        // The remaining copies of the key are bits of a u64
+        let table = self
+            .transpositions
+            .filter(|_| slots.len() <= 64)
+            .map(TranspositionTable::new);
+        DumbSearch {
+            pieces,
+            slots,
+            sums: remaining_sums(pieces),
+            masks: PlacementMasks::new(board, pieces),
+            stack,
+            budget: Budget::new(&options.limits),
+            observer: options.observer.clone(),
+            table,
+            solution_placed: false,
+        }
     }
 }
 
-pub fn step(
-    stats: &mut DumbStats,
-    board: &Board,
-    pieces: &[Vec<Piece>],
-    depth: u16,
-    parent: BoardID,
-) -> SolveResult<DumbFailure> {
-    if pieces.is_empty() {
-        println!("Empty!");
-        return Err(DumbFailure::NoMorePieces);
+/// A board of the search with the placements of the next piece left to try
+struct Frame {
+    board: Board,
+    /// Node of the board in the tree, if it is traced
+    node: Option<BoardID>,
+    /// Index of the next position and transform to try
+    cursor: usize,
+    /// Whether a solution was found below the board
+    solved: bool,
+}
+
+/// Places the pieces in order, trying every position and transform of a piece
+/// before backtracking to the previous one.
+///
+/// The frame at index `depth` of the stack places a copy of `pieces[slots[depth]]`.
+/// Only the frames of a first copy start at cursor 0, so only their boards are in the [TranspositionTable]:
+/// the boards of the other copies leave out the placements before the copy that was placed last
+pub struct DumbSearch<'a> {
+    pieces: &'a [Vec<Piece>],
+    /// The piece of every copy, in the order they are placed
+    slots: Vec<(usize, usize)>,
+    /// The sizes the remaining copies can fill, after the copy at each depth is placed
+    sums: Vec<u128>,
+    masks: PlacementMasks,
+    stack: Vec<Frame>,
+    budget: Budget,
+    observer: Option<Arc<dyn Observer + Send + Sync>>,
+    table: Option<TranspositionTable>,
+    /// Whether the last piece of the returned solution is still placed
+    solution_placed: bool,
+}
+
+impl DumbSearch<'_> {
+    fn notify(&self, event: impl FnOnce(&dyn Observer)) {
+        if let Some(observer) = &self.observer {
+            event(observer.as_ref());
+        }
+    }
+
+    fn progress(&self) -> Progress {
+        let levels = self
+            .stack
+            .iter()
+            .zip(self.slots.iter())
+            .map(|(frame, (piece, _))| {
+                let count = frame.board.field_count() * self.pieces[*piece].len();
+                (frame.cursor.saturating_sub(1), count)
+            });
+        Progress {
+            nodes: self.budget.nodes(),
+            explored: explored(levels),
+        }
+    }
+
+    /// Gets the state of the board before the copy at the depth is placed
+    fn key(&self, board: &Board, depth: usize) -> StateKey {
+        let copies = u64::MAX >> (64 - self.slots.len());
+        StateKey::new(board, copies & u64::MAX << depth)
     }
-    let all_transforms = &pieces[0];
 
-    for y in 0..8 {
-        for x in 0..8 {
+    /// Checks whether the board before the copy at the depth was a dead end before
+    fn is_dead(&self, stats: &mut DumbStats, board: &Board, depth: usize) -> bool {
+        let Some(table) = &self.table else {
+            return false;
+        };
+        let mut ignored = TranspositionStats::default();
+        let counted = match stats.trace {
+            Trace::Off => &mut ignored,
+            _ => &mut stats.transpositions,
+        };
+        table.is_dead(&self.key(board, depth), counted)
+    }
+
+    fn mark_dead(&mut self, stats: &mut DumbStats, board: &Board, depth: usize) {
+        let key = self.key(board, depth);
+        let Some(table) = &mut self.table else {
+            return;
+        };
+        let mut ignored = TranspositionStats::default();
+        let counted = match stats.trace {
+            Trace::Off => &mut ignored,
+            _ => &mut stats.transpositions,
+        };
+        table.mark_dead(key, counted);
+    }
+
+    /// Collects the placement of every frame, which is the one before its cursor
+    fn solution(&self) -> Solution {
+        let placed = self
+            .stack
+            .iter()
+            .zip(self.slots.iter())
+            .map(|(frame, (piece, _))| {
+                let transforms = self.pieces[*piece].len();
+                let placed = frame.cursor - 1;
+                let position = frame.board.position(placed / transforms);
+                (*piece, placed % transforms, position)
+            })
+            .collect();
+        build_solution(&self.stack[0].board, self.pieces, placed)
+    }
+}
+
+impl Search for DumbSearch<'_> {
+    type Stats = DumbStats;
+
+    fn next_solution(&mut self, stats: &mut Self::Stats) -> Option<Solution> {
+        // Like the other solvers, the solution is taken back when the search goes on
+        if std::mem::take(&mut self.solution_placed) {
+            if let Some(depth) = self.stack.len().checked_sub(1) {
+                self.notify(|o| o.backtracked(depth));
+            }
+        }
+        while let Some(depth) = self.stack.len().checked_sub(1) {
+            let frame = &mut self.stack[depth];
+            let (piece, copy) = self.slots[depth];
+            let all_transforms = &self.pieces[piece];
+
             // Try piece on every position and rotation/flipped
-            let pos = Position::new(x, y);
-            for piece in all_transforms.iter() {
-                if board.can_place_piece(pos, piece) {
-                    let mut board_clone = *board;
-                    board_clone.place_piece(pos, piece);
-                    let new_parent = stats.insert_board(Some(parent), board_clone);
-                    if has_single_cells(&board_clone) {
-                        stats.num_skiped_single += 1;
-                        continue;
-                    }
-                    if depth == 4 {
-                        println!("{board_clone}");
-                    }
-                    if board_clone.is_solved() {
-                        return Ok(board_clone);
-                    }
-                    if let Ok(finished) =
-                        step(stats, &board_clone, &pieces[1..], depth + 1, new_parent)
-                    {
-                        return Ok(finished);
+            if frame.cursor == frame.board.field_count() * all_transforms.len() {
+                let frame = self.stack.pop().unwrap();
+                if frame
+                    .node
+                    .is_some_and(|node| stats.tree.children(node).is_empty())
+                {
+                    stats.mark_dead_end(frame.node, DeadEnd::NoPlacement);
+                }
+                if !frame.solved && copy == 0 {
+                    self.mark_dead(stats, &frame.board, depth);
+                }
+                if depth > 0 {
+                    self.notify(|o| o.backtracked(depth - 1));
+                }
+                continue;
+            }
+            let index = frame.cursor / all_transforms.len();
+            let transform = frame.cursor % all_transforms.len();
+            frame.cursor += 1;
+            let next_cursor = frame.cursor;
+
+            let Some(mask) = self.masks.get(piece, transform, index) else {
+                continue;
+            };
+            if !frame.board.can_place_mask(mask) {
+                continue;
+            }
+            let mut board_clone = frame.board;
+            let id = all_transforms[transform].copy_id(copy);
+            board_clone.place_mask(mask, id);
+            let parent = frame.node;
+            let position = frame.board.position(index);
+
+            if !self.budget.spend() {
+                return None;
+            }
+            if self.budget.nodes().is_multiple_of(PROGRESS_INTERVAL) {
+                self.notify(|o| o.progress(self.progress()));
+            }
+            self.notify(|o| o.placed(&all_transforms[transform], position, depth));
+
+            let step = Step {
+                piece: id,
+                transform,
+                position,
+            };
+            let new_parent = stats.insert_board(parent.map(|node| (node, step)), board_clone);
+            let sums = self.sums[depth + 1];
+            // Single cells are only a dead end without pieces of size 1
+            if sums & 1 << 1 == 0 && has_single_cells(&board_clone) {
+                stats.mark_dead_end(new_parent, DeadEnd::SingleCell);
+                self.notify(|o| o.backtracked(depth));
+                continue;
+            }
+            if !regions_fit(&board_clone, sums) {
+                stats.mark_dead_end(new_parent, DeadEnd::Region);
+                self.notify(|o| o.backtracked(depth));
+                continue;
+            }
+            // Like the other solvers, a solution has to use every piece
+            if depth + 1 == self.slots.len() {
+                if board_clone.is_solved() {
+                    for frame in self.stack.iter_mut() {
+                        frame.solved = true;
                     }
+                    self.notify(|o| o.solution(&board_clone));
+                    self.solution_placed = true;
+                    return Some(self.solution());
                 }
+                stats.mark_dead_end(new_parent, DeadEnd::NoPiecesLeft);
+                self.notify(|o| o.backtracked(depth));
+                continue;
+            }
+            // The next copy of the same piece starts after this placement
+            let next_copy = self.slots[depth + 1].0 == piece;
+            if !next_copy && self.is_dead(stats, &board_clone, depth + 1) {
+                self.notify(|o| o.backtracked(depth));
+                continue;
             }
+            let cursor = if next_copy { next_cursor } else { 0 };
+            self.stack.push(Frame {
+                board: board_clone,
+                node: new_parent,
+                cursor,
+                solved: false,
+            });
         }
+        None
+    }
+
+    fn aborted(&self) -> Option<Aborted> {
+        self.budget.aborted()
     }
-    Err(DumbFailure::NotSolvable)
+}
+
+/// Gets the index of the piece and the copy for every depth of the search
+fn copy_slots(pieces: &[Vec<Piece>]) -> Vec<(usize, usize)> {
+    pieces
+        .iter()
+        .enumerate()
+        .flat_map(|(piece, all_transforms)| {
+            let copies = all_transforms.first().map_or(0, Piece::count);
+            (0..copies).map(move |copy| (piece, copy))
+        })
+        .collect()
+}
+
+/// Gets the sizes every subset of the copies can fill, for all suffixes of the copies.
+///
+/// Bit n of a mask is set, if some of the copies have n points together.
+/// The mask at index d is for the copies starting at depth d, the last mask is for no copies at all
+fn remaining_sums(pieces: &[Vec<Piece>]) -> Vec<u128> {
+    let slots = copy_slots(pieces);
+    let mut sums = vec![1u128; slots.len() + 1];
+    for (depth, (piece, _)) in slots.iter().enumerate().rev() {
+        let size = pieces[*piece].first().map_or(0, |t| t.points().len()) as u32;
+        sums[depth] = sums[depth + 1] | sums[depth + 1].checked_shl(size).unwrap_or(0);
+    }
+    sums
+}
+
+/// Checks that the size of every empty region of the board is in the sums of the remaining pieces
+fn regions_fit(board: &Board, sums: u128) -> bool {
+    board
+        .empty_regions()
+        .iter()
+        .all(|region| sums & 1 << region.count_ones() != 0)
 }
 
 fn has_neighbour(board: &Board, position: &Position, direction: (i8, i8)) -> bool {
     let (n_x, n_y) = position.offset(direction);
-    if !(0..=7).contains(&n_x) || !(0..=7).contains(&n_y) {
-        true
-    } else {
-        board.get_value(Position::new(n_x as u8, n_y as u8)) != 0
+    if n_x < 0 || n_y < 0 {
+        return true;
     }
+    !board.is_free(Position::new(n_x as u8, n_y as u8))
 }
 
 fn has_single_cells(board: &Board) -> bool {
-    (0..8)
-        .flat_map(|y| (0..8).map(move |x| (x, y)))
-        .any(|(x, y)| {
-            let position = Position::new(x, y);
-            if board.get_value(position) != 0 {
-                return false;
+    board.positions().any(|position| {
+        if !board.is_free(position) {
+            return false;
+        }
+        // If the cell has on all sides neightbours, we can discard that board
+        [(-1, 0), (0, -1), (1, 0), (0, 1)]
+            .iter()
+            .all(|direction| has_neighbour(board, &position, *direction))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        board::Board,
+        board_tree::DeadEnd,
+        get_game_pieces,
+        piece::Piece,
+        position::Position,
+        solve::{
+            count_solutions, dlx_solver::DlxSolver, parallel_solver::ParallelSolver, piece, solve,
+            straight,
+        },
+    };
+
+    use super::{has_single_cells, remaining_sums, DumbSolver, PlacementMasks, Trace};
+
+    /// Walks the search tree like [DumbSearch](super::DumbSearch) with the given placement function,
+    /// until the limit of boards is reached. Returns the number of placed boards
+    fn walk<F>(
+        board: &Board,
+        pieces: &[Vec<Piece>],
+        depth: usize,
+        limit: &mut usize,
+        place: &F,
+    ) -> usize
+    where
+        F: Fn(&Board, usize, usize, usize) -> Option<Board>,
+    {
+        let mut placed = 0;
+        for index in 0..board.field_count() {
+            for transform in 0..pieces[depth].len() {
+                if *limit == 0 {
+                    return placed;
+                }
+                let Some(next) = place(board, depth, transform, index) else {
+                    continue;
+                };
+                *limit -= 1;
+                placed += 1;
+                if !has_single_cells(&next) && depth + 1 < pieces.len() {
+                    placed += walk(&next, pieces, depth + 1, limit, place);
+                }
             }
-            // If the cell has on all sides neightbours, we can discard that board
-            [(-1, 0), (0, -1), (1, 0), (0, 1)]
-                .iter()
-                .all(|direction| has_neighbour(board, &position, *direction))
-        })
+        }
+        placed
+    }
+
+    fn time_walk<F>(pieces: &[Vec<Piece>], place: F) -> (usize, Duration)
+    where
+        F: Fn(&Board, usize, usize, usize) -> Option<Board>,
+    {
+        let mut limit = 200_000;
+        let start = Instant::now();
+        let placed = walk(&Board::default(), pieces, 0, &mut limit, &place);
+        (placed, start.elapsed())
+    }
+
+    /// Compares checking every point of a piece with the precomputed placement masks.
+    ///
+    /// Run with `cargo test --release -- --ignored --nocapture bench_placement`
+    #[test]
+    #[ignore]
+    fn bench_placement() {
+        let pieces = get_game_pieces()
+            .iter()
+            .map(|p| p.get_all_transforms())
+            .collect::<Vec<_>>();
+        let masks = PlacementMasks::new(&Board::default(), &pieces);
+
+        let (points_placed, points_time) = time_walk(&pieces, |board, piece, transform, index| {
+            let pos = board.position(index);
+            let piece = &pieces[piece][transform];
+            board.can_place_piece(pos, piece).then(|| {
+                let mut next = *board;
+                next.place_piece(pos, piece);
+                next
+            })
+        });
+        let (masks_placed, masks_time) = time_walk(&pieces, |board, piece, transform, index| {
+            let mask = masks.get(piece, transform, index)?;
+            board.can_place_mask(mask).then(|| {
+                let mut next = *board;
+                next.place_mask(mask, pieces[piece][transform].id());
+                next
+            })
+        });
+
+        assert_eq!(points_placed, masks_placed);
+        println!("Placed {points_placed} boards");
+        println!("Points: {points_time:?}");
+        println!("Masks: {masks_time:?}");
+    }
+
+    #[test]
+    fn placement_masks() {
+        let pieces = get_game_pieces()
+            .iter()
+            .map(|p| p.get_all_transforms())
+            .collect::<Vec<_>>();
+        let mut board = Board::new(7, 5);
+        board.set_value(Position::new(2, 1), 20);
+        let masks = PlacementMasks::new(&board, &pieces);
+
+        for (p, all_transforms) in pieces.iter().enumerate() {
+            for (t, piece) in all_transforms.iter().enumerate() {
+                for index in 0..board.field_count() {
+                    let pos = board.position(index);
+                    let can_place = masks
+                        .get(p, t, index)
+                        .is_some_and(|mask| board.can_place_mask(mask));
+                    assert_eq!(can_place, board.can_place_piece(pos, piece));
+                    if can_place {
+                        let mut by_points = board;
+                        by_points.place_piece(pos, piece);
+                        let mut by_mask = board;
+                        by_mask.place_mask(masks.get(p, t, index).unwrap(), piece.id());
+                        assert_eq!(by_points, by_mask);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn region_pruning() {
+        // Sizes 0, 4, 5 and 9 can be filled by the 4 and 5 pieces
+        let pieces = vec![straight(1, 3), straight(2, 4), straight(3, 5)];
+        let sums = remaining_sums(&pieces);
+        assert_eq!(sums[1], 1 | 1 << 4 | 1 << 5 | 1 << 9);
+        assert_eq!(sums[3], 1);
+
+        // The straight pieces fill the 12x1 row in any order, most other placements leave a gap
+        // that the remaining pieces can not fill
+        let board = Board::new(12, 1);
+        let (count, stats) = count_solutions(DumbSolver::default(), &board, &pieces, None);
+        assert_eq!(count, 6);
+        assert!(stats.num_pruned_regions > 0);
+
+        // With a single field piece left, single fields are no dead end
+        let pieces = vec![straight(1, 2), straight(2, 1)];
+        let (count, stats) =
+            count_solutions(DumbSolver::default(), &Board::new(3, 1), &pieces, None);
+        assert_eq!(count, 2);
+        assert_eq!(stats.num_skiped_single, 0);
+    }
+
+    #[test]
+    fn search_trace() {
+        let pieces = vec![straight(1, 3), straight(2, 4), straight(3, 5)];
+        let (result, stats) = solve(DumbSolver::new(Trace::Full), &Board::new(12, 1), &pieces);
+        let solution = result.ok().expect("Row should be solvable");
+
+        let tree = stats.tree();
+        assert_eq!(tree.len(), stats.num_checked_boards);
+        let id = tree.visits(&solution.board)[0];
+        let path = tree.path(id);
+        assert_eq!(path.len(), pieces.len() + 1);
+        assert_eq!(path[0], tree.roots()[0]);
+        assert_eq!(tree.depth(id), pieces.len());
+        assert_eq!(tree.dead_end(id), None);
+        assert_eq!(tree.subtree_size(path[0]), tree.len());
+
+        // Counting walks the whole tree. The 3 piece at x = 1 leaves a single field
+        let (_, stats) = count_solutions(
+            DumbSolver::new(Trace::Full),
+            &Board::new(12, 1),
+            &pieces,
+            None,
+        );
+        let tree = stats.tree();
+        let mut gap = Board::new(12, 1);
+        gap.place_piece(Position::new(1, 0), &pieces[0][0]);
+        assert_eq!(
+            tree.dead_end(tree.visits(&gap)[0]),
+            Some(DeadEnd::SingleCell)
+        );
+
+        let tree_stats = tree.stats();
+        assert_eq!(tree_stats.nodes, tree.len());
+        assert_eq!(tree_stats.max_depth(), pieces.len());
+        assert_eq!(tree_stats.nodes_per_depth[0], 1);
+        assert_eq!(
+            tree_stats.dead_ends,
+            stats.num_skiped_single + stats.num_pruned_regions
+        );
+    }
+
+    #[test]
+    fn trace_levels() {
+        let pieces = vec![straight(1, 3), straight(2, 4), straight(3, 5)];
+        let board = Board::new(12, 1);
+        let count = |trace| count_solutions(DumbSolver::new(trace), &board, &pieces, None);
+
+        let (solutions, full) = count(Trace::Full);
+        assert_eq!(full.tree().len(), full.num_checked_boards);
+
+        let (count_off, off) = count(Trace::Off);
+        assert_eq!(count_off, solutions);
+        assert_eq!(off.num_checked_boards + off.num_pruned_regions, 0);
+        assert!(off.tree().is_empty());
+
+        let (_, counters) = count(Trace::Counters);
+        assert_eq!(counters.num_checked_boards, full.num_checked_boards);
+        assert_eq!(counters.num_pruned_regions, full.num_pruned_regions);
+        assert!(counters.tree().is_empty());
+
+        let (_, ring) = count(Trace::Ring(10));
+        assert_eq!(ring.num_checked_boards, full.num_checked_boards);
+        assert_eq!(ring.tree().len(), 10);
+        assert_eq!(ring.tree().stats().nodes, 10);
+
+        // The traces of the threads are merged into one ring
+        let solver = ParallelSolver::new(DumbSolver::new(Trace::Ring(10))).with_threads(2);
+        let (_, parallel) = count_solutions(solver, &board, &pieces, None);
+        assert_eq!(parallel.workers.tree().len(), 10);
+    }
+
+    #[test]
+    fn transpositions() {
+        let board = Board::new(5, 4);
+        let pieces = vec![
+            piece(1, &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]),
+            piece(2, &[(0, 0), (1, 0), (2, 0), (3, 0), (0, 1)]),
+            piece(3, &[(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)]),
+            piece(4, &[(0, 0), (1, 0), (1, 1), (2, 1), (3, 1)]),
+        ];
+        let (expected, _) = count_solutions(DlxSolver, &board, &pieces, None);
+        assert!(expected > 0);
+
+        let (count, plain) = count_solutions(DumbSolver::default(), &board, &pieces, None);
+        assert_eq!(count, expected);
+        let solver = DumbSolver::default().with_transpositions(1000);
+        let (count, table) = count_solutions(solver, &board, &pieces, None);
+        assert_eq!(count, expected);
+        assert!(table.transpositions.hits > 0);
+        assert!(table.num_checked_boards < plain.num_checked_boards);
+
+        // Only the boards before a first copy are looked up, the copies still count once
+        let pieces = vec![straight(1, 2), straight(2, 2)]
+            .into_iter()
+            .map(|all_transforms| {
+                all_transforms
+                    .into_iter()
+                    .map(|transform| transform.with_count(3))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let board = Board::new(4, 3);
+        let (expected, _) = count_solutions(DlxSolver, &board, &pieces, None);
+        let solver = DumbSolver::default().with_transpositions(1000);
+        let (count, table) = count_solutions(solver, &board, &pieces, None);
+        assert_eq!(count, expected);
+        assert!(table.transpositions.misses > 0);
+    }
 }
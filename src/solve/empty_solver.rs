@@ -1,6 +1,8 @@
 use std::fmt::Display;
 
-use super::Solvable;
+use crate::solution::Solution;
+
+use super::{Solvable, SolveOptions};
 
 struct EmptySolver;
 
@@ -23,7 +25,22 @@ impl Solvable for EmptySolver {
         _: &mut Self::SolveStats,
         board: &crate::board::Board,
         _: &[Vec<crate::piece::Piece>],
+        _: &SolveOptions,
     ) -> super::SolveResult<Self::Failure> {
-        Ok(*board)
+        Ok(Solution::new(board, Vec::new()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Board, solve::solve};
+
+    use super::EmptySolver;
+
+    #[test]
+    fn returns_board() {
+        let board = Board::default();
+        let (result, _) = solve(EmptySolver, &board, &[]);
+        assert!(result.is_ok_and(|s| s.board == board && s.placements.is_empty()));
     }
 }
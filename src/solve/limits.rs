@@ -0,0 +1,211 @@
+use std::{
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// How many nodes are visited between two checks of the deadline and the cancel token
+const CHECK_INTERVAL: usize = 1024;
+
+/// Shared flag to cancel a running search from another thread
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<TokenState>);
+
+#[derive(Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    parent: Option<CancelToken>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a token that is cancelled together with this token, but can also be cancelled on its own
+    pub fn child(&self) -> Self {
+        Self(Arc::new(TokenState {
+            cancelled: AtomicBool::new(false),
+            parent: Some(self.clone()),
+        }))
+    }
+
+    /// Asks every search holding a clone of this token or of a child to stop
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Relaxed)
+            || self
+                .0
+                .parent
+                .as_ref()
+                .is_some_and(CancelToken::is_cancelled)
+    }
+}
+
+/// Limits of a search. The search is aborted as soon as one of them is reached.
+///
+/// The default has no limits.
+#[derive(Clone, Default)]
+pub struct Limits {
+    pub deadline: Option<Instant>,
+    pub max_nodes: Option<usize>,
+    pub cancel: Option<CancelToken>,
+    /// Node counter shared by searches running in parallel, so the node limit applies to all of them
+    pub(crate) shared_nodes: Option<Arc<AtomicUsize>>,
+}
+
+impl Limits {
+    /// Sets the deadline to the timeout from now
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Checks the limits that do not depend on the number of nodes
+    fn check(&self) -> Option<AbortReason> {
+        if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            Some(AbortReason::Cancelled)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Some(AbortReason::TimedOut)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AbortReason {
+    Cancelled,
+    TimedOut,
+    NodeLimit,
+}
+
+/// Why a search stopped early and how many nodes it visited until then
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Aborted {
+    pub reason: AbortReason,
+    pub nodes: usize,
+}
+
+impl Display for Aborted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.reason {
+            AbortReason::Cancelled => write!(f, "Cancelled after {} nodes", self.nodes),
+            AbortReason::TimedOut => write!(f, "Timed out after {} nodes", self.nodes),
+            AbortReason::NodeLimit => write!(f, "Node limit reached after {} nodes", self.nodes),
+        }
+    }
+}
+
+/// Counts the nodes of a search and checks the [Limits] on every node
+pub(crate) struct Budget {
+    limits: Limits,
+    nodes: usize,
+    aborted: Option<Aborted>,
+}
+
+impl Budget {
+    pub(crate) fn new(limits: &Limits) -> Self {
+        Self {
+            limits: limits.clone(),
+            nodes: 0,
+            aborted: None,
+        }
+    }
+
+    /// Counts the next node. Returns false if the search has to stop before visiting it
+    pub(crate) fn spend(&mut self) -> bool {
+        if self.aborted.is_some() {
+            return false;
+        }
+        // Every search takes its own number from the shared counter, so together they stay in the limit
+        let nodes = match &self.limits.shared_nodes {
+            Some(shared) => shared.fetch_add(1, Ordering::Relaxed),
+            None => self.nodes,
+        };
+        let reason = if self.limits.max_nodes.is_some_and(|max| nodes >= max) {
+            Some(AbortReason::NodeLimit)
+        } else if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            self.limits.check()
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            self.aborted = Some(Aborted { reason, nodes });
+            return false;
+        }
+        self.nodes += 1;
+        true
+    }
+
+    /// Number of nodes counted so far
+    pub(crate) fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    pub(crate) fn aborted(&self) -> Option<Aborted> {
+        self.aborted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{AbortReason, Budget, CancelToken, Limits};
+
+    #[test]
+    fn budget() {
+        let mut budget = Budget::new(&Limits::default().with_max_nodes(3));
+        assert!((0..3).all(|_| budget.spend()));
+        assert!(!budget.spend());
+        assert!(!budget.spend());
+        let aborted = budget.aborted().unwrap();
+        assert_eq!((aborted.reason, aborted.nodes), (AbortReason::NodeLimit, 3));
+        assert_eq!(aborted.to_string(), "Node limit reached after 3 nodes");
+
+        let cancel = CancelToken::new();
+        let child = cancel.child();
+        let mut budget = Budget::new(&Limits::default().with_cancel(child.clone()));
+        assert!(budget.spend());
+        cancel.cancel();
+        assert!(child.is_cancelled());
+        let parent = CancelToken::new();
+        parent.child().cancel();
+        assert!(!parent.is_cancelled());
+        // The token is only checked every few nodes
+        while budget.spend() {}
+        assert_eq!(budget.aborted().unwrap().reason, AbortReason::Cancelled);
+
+        let limits = Limits {
+            deadline: Some(Instant::now()),
+            ..Default::default()
+        };
+        let mut budget = Budget::new(&limits);
+        assert!(!budget.spend());
+        assert_eq!(budget.aborted().unwrap().reason, AbortReason::TimedOut);
+
+        let mut budget = Budget::new(&Limits::default().with_timeout(Duration::from_secs(60)));
+        assert!((0..10_000).all(|_| budget.spend()));
+        assert!(budget.aborted().is_none());
+    }
+}
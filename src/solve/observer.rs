@@ -0,0 +1,65 @@
+use crate::{board::Board, piece::Piece, position::Position};
+
+/// How many nodes are visited between two [Observer::progress] events
+pub const PROGRESS_INTERVAL: usize = 10_000;
+
+/// How far a search got
+#[derive(Clone, Copy, Debug)]
+pub struct Progress {
+    /// Nodes visited so far
+    pub nodes: usize,
+    /// Estimated part of the search tree that was explored, between 0 and 1.
+    ///
+    /// The estimate assumes that all branches at one level are of the same size
+    pub explored: f64,
+}
+
+/// Receives the events of a search.
+///
+/// All events do nothing by default, so only the interesting ones have to be implemented.
+/// The depth of an event is the number of pieces placed before the piece of the event.
+/// The pieces of a solution stay placed until the search goes on,
+/// so a search that is not continued after a solution sends no [Observer::backtracked] for them.
+pub trait Observer {
+    /// A piece was placed with its top left corner at the position
+    fn placed(&self, _piece: &Piece, _position: Position, _depth: usize) {}
+
+    /// The piece placed at the depth was removed again
+    fn backtracked(&self, _depth: usize) {}
+
+    /// A solution was found
+    fn solution(&self, _board: &Board) {}
+
+    /// Sent every [PROGRESS_INTERVAL] nodes
+    fn progress(&self, _progress: Progress) {}
+}
+
+/// Adds up the explored part of every level.
+///
+/// Every level is the index of the branch that is explored and the number of branches.
+/// Each branch of a level covers an equal part of the current branch of the level above.
+pub(crate) fn explored(levels: impl Iterator<Item = (usize, usize)>) -> f64 {
+    let mut explored = 0.0;
+    let mut branch_size = 1.0;
+    for (index, count) in levels {
+        if count == 0 {
+            break;
+        }
+        branch_size /= count as f64;
+        explored += index.min(count) as f64 * branch_size;
+    }
+    explored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::explored;
+
+    #[test]
+    fn explored_levels() {
+        assert_eq!(explored([].into_iter()), 0.0);
+        assert_eq!(explored([(1, 2)].into_iter()), 0.5);
+        assert_eq!(explored([(1, 2), (3, 4)].into_iter()), 0.875);
+        assert_eq!(explored([(0, 4), (1, 2), (0, 0)].into_iter()), 0.125);
+    }
+}
@@ -0,0 +1,624 @@
+use std::{
+    fmt::Display,
+    num::NonZero,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{sync_channel, Receiver, SyncSender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    board::Board,
+    piece::Piece,
+    position::Position,
+    solution::{Placement, Solution},
+};
+
+use super::{
+    limits::{AbortReason, Aborted, Budget, CancelToken},
+    observer::{Observer, Progress, PROGRESS_INTERVAL},
+    Enumerable, Search, Solvable, SolveOptions, SolveResult,
+};
+
+/// Number of solutions a worker can find before it has to wait until they are taken
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Stats that can be added up from several searches
+pub trait MergeStats {
+    fn merge(&mut self, other: Self);
+}
+
+#[derive(Default)]
+pub struct ParallelStats<T> {
+    /// Number of placements of the first copy of the task piece, each searched as its own task
+    pub tasks: usize,
+    /// Stats of all tasks, merged together
+    pub workers: T,
+}
+
+impl<T: Display> Display for ParallelStats<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Tasks: {}", self.tasks)?;
+        write!(f, "{}", self.workers)
+    }
+}
+
+pub enum ParallelFailure {
+    NoMorePieces,
+    NotSolvable,
+    Aborted(Aborted),
+}
+
+impl Display for ParallelFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParallelFailure::NoMorePieces => writeln!(f, "No more pieces"),
+            ParallelFailure::NotSolvable => writeln!(f, "Not Solvable"),
+            ParallelFailure::Aborted(aborted) => writeln!(f, "{aborted}"),
+        }
+    }
+}
+
+/// Runs another solver on several threads.
+///
+/// Every placement of the first copy of the task piece, the piece with the fewest copies, is a task,
+/// that the inner solver solves with the other pieces and the later copies.
+/// The threads take the tasks one after another, until all of them are done.
+/// The first copy comes before the later ones in the (position, transform) order that solvers label copies in.
+/// A solution of a task with a later copy before the placed one is dropped, it is found by the task of that copy.
+/// So no solution is found twice.
+/// Solutions are found in no particular order, but the number of solutions is always the same.
+#[derive(Clone, Copy)]
+pub struct ParallelSolver<S> {
+    pub solver: S,
+    pub threads: usize,
+}
+
+impl<S> ParallelSolver<S> {
+    /// Uses as many threads as the system can run in parallel
+    pub fn new(solver: S) -> Self {
+        let threads = thread::available_parallelism().map_or(1, NonZero::get);
+        Self { solver, threads }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+}
+
+impl<S> Solvable for ParallelSolver<S>
+where
+    S: Enumerable + Clone + Send + Sync + 'static,
+    S::SolveStats: MergeStats + Send + 'static,
+{
+    type SolveStats = ParallelStats<S::SolveStats>;
+    type Failure = ParallelFailure;
+
+    fn solve(
+        &self,
+        stats: &mut Self::SolveStats,
+        board: &Board,
+        pieces: &[Vec<Piece>],
+        options: &SolveOptions,
+    ) -> SolveResult<Self::Failure> {
+        if pieces.is_empty() {
+            return Err(ParallelFailure::NoMorePieces);
+        }
+
+        let mut search = self.search(stats, board, pieces, options);
+        let solution = search.next_solution(stats);
+        // Stop the other workers as soon as one solution is found
+        search.finish(stats);
+        solution.ok_or_else(|| {
+            search
+                .aborted()
+                .map_or(ParallelFailure::NotSolvable, ParallelFailure::Aborted)
+        })
+    }
+}
+
+impl<S> Enumerable for ParallelSolver<S>
+where
+    S: Enumerable + Clone + Send + Sync + 'static,
+    S::SolveStats: MergeStats + Send + 'static,
+{
+    type Search<'a> = ParallelSearch<S>;
+
+    fn search<'a>(
+        &self,
+        stats: &mut Self::SolveStats,
+        board: &Board,
+        pieces: &'a [Vec<Piece>],
+        options: &SolveOptions,
+    ) -> Self::Search<'a> {
+        // The piece with the fewest copies, whose first copy the tasks place
+        let mut rest = pieces.to_vec();
+        let mut task_piece = Vec::new();
+        if let Some(piece) =
+            (0..pieces.len()).min_by_key(|piece| pieces[*piece].first().map_or(0, Piece::count))
+        {
+            task_piece = rest.remove(piece);
+            let later = task_piece
+                .iter()
+                .filter_map(Piece::later_copies)
+                .collect::<Vec<_>>();
+            if !later.is_empty() {
+                rest.insert(piece, later);
+            }
+        }
+        // The workers are stopped with their own token, which also stops if the caller cancels
+        let user_cancel = options.limits.cancel.clone();
+        let cancel = user_cancel
+            .as_ref()
+            .map_or_else(CancelToken::new, CancelToken::child);
+        let mut options = options.clone();
+        options.limits.cancel = Some(cancel.clone());
+        if options.limits.max_nodes.is_some() {
+            options.limits.shared_nodes = Some(Arc::default());
+        }
+        // Placing the tasks counts against the limits, like the nodes of the workers
+        let mut budget = Budget::new(&options.limits);
+        let tasks = task_placements(board, &task_piece, pieces.len() == 1, &mut budget);
+        stats.tasks += tasks.len();
+
+        let shared = Arc::new(Shared {
+            solver: self.solver.clone(),
+            pieces: rest,
+            task_piece,
+            tasks,
+            next_task: AtomicUsize::new(0),
+            options,
+            progress_nodes: Arc::default(),
+            user_cancel,
+            cancel,
+            aborted: Mutex::new(budget.aborted()),
+        });
+
+        let (sender, receiver) = sync_channel(CHANNEL_CAPACITY);
+        let workers = (0..self.threads.min(shared.tasks.len()))
+            .map(|_| {
+                let shared = shared.clone();
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    let stats = shared.work(&sender);
+                    // The search may have been dropped already
+                    let _ = sender.send(Message::Done(stats));
+                })
+            })
+            .collect::<Vec<_>>();
+
+        ParallelSearch {
+            receiver,
+            running: workers.len(),
+            workers,
+            shared,
+        }
+    }
+}
+
+/// Boards with the first copy of the piece placed, for every position and transform that fits.
+///
+/// The later copies only cover fields from the position of the first copy on.
+/// So if the piece is alone, boards with a free field before that position are left out.
+///
+/// Every placement is a node of the budget. Returns no tasks if the budget is used up
+fn task_placements(
+    board: &Board,
+    transforms: &[Piece],
+    alone: bool,
+    budget: &mut Budget,
+) -> Vec<(Board, Placement)> {
+    let mut tasks = Vec::new();
+    for index in 0..board.field_count() {
+        let position = board.position(index);
+        for (transform, shape) in transforms.iter().enumerate() {
+            let Some(mask) = board.piece_mask(position, shape) else {
+                continue;
+            };
+            let Some(placement) = Placement::new(shape, transform, position) else {
+                continue;
+            };
+            if !board.can_place_mask(mask) {
+                continue;
+            }
+            if !budget.spend() {
+                return Vec::new();
+            }
+            let mut next = *board;
+            next.place_mask(mask, placement.piece);
+            if alone && (0..index).any(|before| next.is_free(next.position(before))) {
+                continue;
+            }
+            tasks.push((next, placement));
+        }
+    }
+    tasks
+}
+
+enum Message<T> {
+    Solution(Solution),
+    /// The worker has no tasks left and sends its stats
+    Done(T),
+}
+
+/// Everything the workers share
+struct Shared<S: Enumerable> {
+    solver: S,
+    /// The other pieces and the later copies of the task piece
+    pieces: Vec<Vec<Piece>>,
+    /// All transforms of the piece whose first copy the tasks place
+    task_piece: Vec<Piece>,
+    /// The board with the first copy of the task piece placed and its placement
+    tasks: Vec<(Board, Placement)>,
+    next_task: AtomicUsize,
+    options: SolveOptions,
+    progress_nodes: Arc<AtomicUsize>,
+    /// The token of the caller, if there is one
+    user_cancel: Option<CancelToken>,
+    /// Stops all workers
+    cancel: CancelToken,
+    aborted: Mutex<Option<Aborted>>,
+}
+
+impl<S: Enumerable> Shared<S> {
+    /// Solves tasks until there are none left or the search is stopped
+    fn work(&self, sender: &SyncSender<Message<S::SolveStats>>) -> S::SolveStats {
+        let mut stats = S::SolveStats::default();
+        loop {
+            if self.cancel.is_cancelled() && !self.user_cancelled() {
+                break;
+            }
+            let task = self.next_task.fetch_add(1, Ordering::Relaxed);
+            let Some((board, placed)) = self.tasks.get(task) else {
+                break;
+            };
+            // The placement of the task is the first level of the search
+            if let Some(observer) = &self.options.observer {
+                observer.placed(&self.task_piece[placed.transform], placed.anchor, 0);
+            }
+            let done = self.solve_task(task, board, placed, &mut stats, sender);
+            if let Some(observer) = &self.options.observer {
+                observer.backtracked(0);
+            }
+            if !done {
+                break;
+            }
+        }
+        stats
+    }
+
+    /// Sends the solutions of the task. Returns false if the worker has to stop
+    fn solve_task(
+        &self,
+        task: usize,
+        board: &Board,
+        placed: &Placement,
+        stats: &mut S::SolveStats,
+        sender: &SyncSender<Message<S::SolveStats>>,
+    ) -> bool {
+        if self.pieces.is_empty() {
+            // The task piece was the only one
+            if !board.is_solved() {
+                return true;
+            }
+            if let Some(observer) = &self.options.observer {
+                observer.solution(board);
+            }
+            let solution = Solution::new(board, vec![placed.clone()]);
+            return sender.send(Message::Solution(solution)).is_ok();
+        }
+
+        let options = self.task_options(task);
+        let copies = self.task_piece[placed.transform].ids();
+        let order = |p: &Placement| (board.index(p.anchor), p.transform);
+        let mut search = self.solver.search(stats, board, &self.pieces, &options);
+        while let Some(solution) = search.next_solution(stats) {
+            let first = solution
+                .placements
+                .iter()
+                .filter(|p| p.piece != placed.piece && copies.contains(&p.piece))
+                .all(|p| order(p) > order(placed));
+            if !first {
+                continue;
+            }
+            if let Some(observer) = &self.options.observer {
+                observer.solution(&solution.board);
+            }
+            // The board of the solution already has the task piece
+            let mut placements = solution.placements;
+            placements.push(placed.clone());
+            let solution = Solution::new(&solution.board, placements);
+            if sender.send(Message::Solution(solution)).is_err() {
+                return false;
+            }
+        }
+        if let Some(aborted) = search.aborted() {
+            self.abort(aborted);
+            return false;
+        }
+        true
+    }
+
+    fn user_cancelled(&self) -> bool {
+        self.user_cancel
+            .as_ref()
+            .is_some_and(CancelToken::is_cancelled)
+    }
+
+    /// Stops all workers, because one of the limits was reached
+    fn abort(&self, aborted: Aborted) {
+        // Workers stopped by the parallel search itself were not aborted by a limit
+        if aborted.reason == AbortReason::Cancelled && !self.user_cancelled() {
+            return;
+        }
+        self.aborted.lock().unwrap().get_or_insert(aborted);
+        self.cancel.cancel();
+    }
+
+    fn task_options(&self, task: usize) -> SolveOptions {
+        let mut options = self.options.clone();
+        options.observer = self.options.observer.clone().map(|observer| {
+            Arc::new(TaskObserver {
+                observer,
+                task,
+                tasks: self.tasks.len(),
+                nodes: self.progress_nodes.clone(),
+            }) as Arc<dyn Observer + Send + Sync>
+        });
+        options
+    }
+}
+
+/// Passes the events of the inner search of a task on, below the placement of the task.
+///
+/// The worker sends the events of the placement of the task itself,
+/// and the solutions once they are known to belong to the task
+struct TaskObserver {
+    observer: Arc<dyn Observer + Send + Sync>,
+    task: usize,
+    tasks: usize,
+    /// Nodes visited by all workers, counted with the progress events
+    nodes: Arc<AtomicUsize>,
+}
+
+impl Observer for TaskObserver {
+    fn placed(&self, piece: &Piece, position: Position, depth: usize) {
+        self.observer.placed(piece, position, depth + 1);
+    }
+
+    fn backtracked(&self, depth: usize) {
+        self.observer.backtracked(depth + 1);
+    }
+
+    fn solution(&self, _board: &Board) {}
+
+    fn progress(&self, progress: Progress) {
+        let nodes = self.nodes.fetch_add(PROGRESS_INTERVAL, Ordering::Relaxed) + PROGRESS_INTERVAL;
+        self.observer.progress(Progress {
+            nodes,
+            explored: (self.task as f64 + progress.explored) / self.tasks as f64,
+        });
+    }
+}
+
+/// Receives the solutions of the workers.
+///
+/// Dropping the search stops the workers
+pub struct ParallelSearch<S: Enumerable> {
+    receiver: Receiver<Message<S::SolveStats>>,
+    workers: Vec<JoinHandle<()>>,
+    /// Number of workers that did not send their stats yet
+    running: usize,
+    shared: Arc<Shared<S>>,
+}
+
+impl<S: Enumerable> ParallelSearch<S>
+where
+    S::SolveStats: MergeStats,
+{
+    /// Stops the workers and merges their stats
+    pub fn finish(&mut self, stats: &mut ParallelStats<S::SolveStats>) {
+        self.shared.cancel.cancel();
+        while self.next_solution(stats).is_some() {}
+    }
+}
+
+impl<S: Enumerable> Search for ParallelSearch<S>
+where
+    S::SolveStats: MergeStats,
+{
+    type Stats = ParallelStats<S::SolveStats>;
+
+    fn next_solution(&mut self, stats: &mut Self::Stats) -> Option<Solution> {
+        while self.running > 0 {
+            match self.receiver.recv() {
+                Ok(Message::Solution(solution)) => return Some(solution),
+                Ok(Message::Done(worker_stats)) => {
+                    stats.workers.merge(worker_stats);
+                    self.running -= 1;
+                }
+                Err(_) => panic!("Worker of the parallel search panicked"),
+            }
+        }
+        None
+    }
+
+    fn aborted(&self) -> Option<Aborted> {
+        *self.shared.aborted.lock().unwrap()
+    }
+}
+
+impl<S: Enumerable> Drop for ParallelSearch<S> {
+    fn drop(&mut self) {
+        self.shared.cancel.cancel();
+        // Take the remaining messages, so no worker waits on the channel
+        while self.running > 0 {
+            match self.receiver.recv() {
+                Ok(Message::Done(_)) => self.running -= 1,
+                Ok(Message::Solution(_)) => {}
+                Err(_) => break,
+            }
+        }
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        board::Board,
+        piece::Piece,
+        position::Position,
+        solution::Solution,
+        solve::{
+            count_solutions,
+            dlx_solver::DlxSolver,
+            dumb_solver::DumbSolver,
+            limits::{AbortReason, CancelToken, Limits},
+            piece, solve, solve_all, solve_with_options, SolveOptions,
+        },
+    };
+
+    use super::{ParallelFailure, ParallelSolver};
+
+    /// The 4x4 square filled with a P pentomino, a square, and two straight pieces
+    fn square_puzzle() -> (Board, Vec<Vec<Piece>>) {
+        let pieces = vec![
+            piece(1, &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]),
+            piece(2, &[(0, 0), (1, 0), (0, 1), (1, 1)]),
+            piece(3, &[(0, 0), (1, 0), (2, 0), (3, 0)]),
+            piece(4, &[(0, 0), (1, 0), (2, 0)]),
+        ];
+        (Board::new(4, 4), pieces)
+    }
+
+    #[test]
+    fn count_is_deterministic() {
+        let (board, pieces) = square_puzzle();
+        let (expected, _) = count_solutions(DlxSolver, &board, &pieces, None);
+        assert!(expected > 0);
+
+        for threads in 1..=4 {
+            let solver = ParallelSolver::new(DlxSolver).with_threads(threads);
+            let (count, stats) = count_solutions(solver, &board, &pieces, None);
+            assert_eq!(count, expected);
+            assert!(stats.tasks > 0);
+            assert!(stats.workers.nodes_visited > 0);
+
+            let solver = ParallelSolver::new(DumbSolver::default()).with_threads(threads);
+            assert_eq!(count_solutions(solver, &board, &pieces, None).0, expected);
+        }
+
+        // A single piece is placed by the tasks alone
+        let board = Board::new(2, 1);
+        let pieces = vec![piece(1, &[(0, 0), (1, 0)])];
+        let solver = ParallelSolver::new(DlxSolver).with_threads(2);
+        assert_eq!(count_solutions(solver, &board, &pieces, None).0, 1);
+    }
+
+    #[test]
+    fn copies_are_placed_once() {
+        // Three copies of the domino fill the 3x2 rectangle in 3 ways.
+        // The first copy has to cover the top left field, each of its 2 placements is a task
+        let board = Board::new(3, 2);
+        let domino = Piece::new(1, vec![Position::new(0, 0), Position::new(1, 0)]).with_count(3);
+        let pieces = vec![domino.get_all_transforms()];
+        let solver = ParallelSolver::new(DlxSolver).with_threads(2);
+        let (count, stats) = count_solutions(solver, &board, &pieces, None);
+        assert_eq!((count, stats.tasks), (3, 2));
+        let boards = |solutions: Vec<Solution>| {
+            let mut boards = solutions.into_iter().map(|s| s.board).collect::<Vec<_>>();
+            boards.sort_by(|a, b| a.as_slice().cmp(b.as_slice()));
+            boards
+        };
+        assert_eq!(
+            boards(solve_all(solver, &board, &pieces).collect()),
+            boards(solve_all(DlxSolver, &board, &pieces).collect()),
+            "Copies are labeled like the other solvers do"
+        );
+
+        // The tasks place the square, the copies of the domino are left to the inner solver
+        let board = Board::new(4, 2);
+        let domino = domino.with_count(2);
+        let pieces = vec![
+            domino.get_all_transforms(),
+            piece(3, &[(0, 0), (1, 0), (0, 1), (1, 1)]),
+        ];
+        let (expected, _) = count_solutions(DlxSolver, &board, &pieces, None);
+        let (count, stats) = count_solutions(solver, &board, &pieces, None);
+        assert_eq!((count, stats.tasks), (expected, 3));
+        assert_eq!(count, 5);
+
+        // With copies of every piece, the inner solver also places the later copies of the task piece
+        let board = Board::new(4, 3);
+        let square = pieces[1][0].clone().with_count(2);
+        let pieces = vec![domino.get_all_transforms(), square.get_all_transforms()];
+        let (expected, _) = count_solutions(DlxSolver, &board, &pieces, None);
+        assert!(expected > 0);
+        for solver in [solver, solver.with_threads(1)] {
+            assert_eq!(count_solutions(solver, &board, &pieces, None).0, expected);
+        }
+        let dumb = ParallelSolver::new(DumbSolver::default()).with_threads(2);
+        assert_eq!(count_solutions(dumb, &board, &pieces, None).0, expected);
+
+        // Only the first of the 16 squares is placed by the tasks, the inner solver places the others
+        let board = Board::default();
+        let square = square.with_count(16);
+        let pieces = vec![square.get_all_transforms()];
+        let (count, stats) = count_solutions(solver, &board, &pieces, None);
+        assert_eq!((count, stats.tasks), (1, 1));
+    }
+
+    #[test]
+    fn first_solution() {
+        let (board, pieces) = square_puzzle();
+        let solver = ParallelSolver::new(DumbSolver::default()).with_threads(3);
+        let (result, stats) = solve(solver, &board, &pieces);
+        assert!(result.is_ok_and(|s| s.board.is_solved() && s.placements.len() == pieces.len()));
+        assert!(stats.workers.num_checked_boards > 0);
+
+        // The straight piece of length 4 does not fit into a 3x3 square
+        let board = Board::new(3, 3);
+        let (result, _) = solve(ParallelSolver::new(DlxSolver), &board, &pieces[2..]);
+        assert!(matches!(result, Err(ParallelFailure::NotSolvable)));
+    }
+
+    #[test]
+    fn abort() {
+        let (board, pieces) = square_puzzle();
+        let solver = ParallelSolver::new(DlxSolver).with_threads(2);
+
+        let limits = Limits::default().with_max_nodes(2);
+        let options = SolveOptions::default().with_limits(limits);
+        let (result, _) = solve_with_options(solver, &board, &pieces, &options);
+        let Err(ParallelFailure::Aborted(aborted)) = result else {
+            panic!("Search should be aborted");
+        };
+        assert_eq!(aborted.reason, AbortReason::NodeLimit);
+        assert!(aborted.nodes >= 2);
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let options = SolveOptions::default().with_limits(Limits::default().with_cancel(cancel));
+        let (result, _) = solve_with_options(solver, &board, &pieces, &options);
+        assert!(
+            matches!(result, Err(ParallelFailure::Aborted(a)) if a.reason == AbortReason::Cancelled)
+        );
+
+        // The limits also apply while the tasks are placed
+        let limits = Limits::default().with_timeout(Duration::ZERO);
+        let options = SolveOptions::default().with_limits(limits);
+        let (result, stats) = solve_with_options(solver, &board, &pieces, &options);
+        assert!(
+            matches!(result, Err(ParallelFailure::Aborted(a)) if a.reason == AbortReason::TimedOut)
+        );
+        assert_eq!(stats.tasks, 0);
+    }
+}
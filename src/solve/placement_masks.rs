@@ -0,0 +1,35 @@
+use crate::{board::Board, piece::Piece};
+
+/// Precomputed bit masks of every placement of every piece transform.
+///
+/// A placement is a transform of a piece at one of the positions of the board.
+/// Placements where the transform does not fit on the board have no mask
+pub struct PlacementMasks {
+    masks: Vec<Vec<Vec<Option<u64>>>>,
+}
+
+impl PlacementMasks {
+    /// Computes the masks for all transforms of all pieces on the board
+    pub fn new(board: &Board, pieces: &[Vec<Piece>]) -> Self {
+        let masks = pieces
+            .iter()
+            .map(|all_transforms| {
+                all_transforms
+                    .iter()
+                    .map(|piece| {
+                        board
+                            .positions()
+                            .map(|position| board.piece_mask(position, piece))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { masks }
+    }
+
+    /// Gets the mask of the transform of the piece placed at the position with the given index
+    pub fn get(&self, piece: usize, transform: usize, index: usize) -> Option<u64> {
+        self.masks[piece][transform][index]
+    }
+}
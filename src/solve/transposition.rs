@@ -0,0 +1,145 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Display,
+};
+
+use crate::board::Board;
+
+use super::parallel_solver::MergeStats;
+
+/// State of a search: the covered fields of the board and the pieces that are left to place.
+///
+/// Two searches in the same state find the same solutions, no matter in which order the pieces were placed.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct StateKey {
+    covered: u64,
+    remaining: u64,
+}
+
+impl StateKey {
+    /// Bit n of remaining is set if piece n is not placed yet.
+    /// Every piece covers at least one field, so there can not be more than 64 pieces left on a solvable board
+    pub fn new(board: &Board, remaining: u64) -> Self {
+        Self {
+            covered: board.occupied() | board.blocked(),
+            remaining,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct TranspositionStats {
+    /// States that were skipped, because they were dead before
+    pub hits: usize,
+    /// States that were looked up, but were not known to be dead
+    pub misses: usize,
+    /// Dead states that were dropped to stay in the capacity
+    pub evictions: usize,
+}
+
+impl MergeStats for TranspositionStats {
+    fn merge(&mut self, other: Self) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.evictions += other.evictions;
+    }
+}
+
+impl Display for TranspositionStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Transposition hits: {}", self.hits)?;
+        writeln!(f, "Transposition misses: {}", self.misses)?;
+        writeln!(f, "Transposition evictions: {}", self.evictions)
+    }
+}
+
+/// Remembers states of a search that have no solution, so reaching them again can be skipped.
+///
+/// When the table is full, the state that was added first is dropped.
+pub struct TranspositionTable {
+    capacity: usize,
+    dead: HashSet<StateKey>,
+    order: VecDeque<StateKey>,
+}
+
+impl TranspositionTable {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            dead: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Looks up the state, counting a hit if it is known to have no solution
+    pub fn is_dead(&self, key: &StateKey, stats: &mut TranspositionStats) -> bool {
+        let dead = self.dead.contains(key);
+        if dead {
+            stats.hits += 1;
+        } else {
+            stats.misses += 1;
+        }
+        dead
+    }
+
+    /// Remembers that the state has no solution
+    pub fn mark_dead(&mut self, key: StateKey, stats: &mut TranspositionStats) {
+        if self.capacity == 0 || !self.dead.insert(key) {
+            return;
+        }
+        self.order.push_back(key);
+        while self.dead.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.dead.remove(&oldest);
+                stats.evictions += 1;
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.dead.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dead.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Board, piece::Piece, position::Position};
+
+    use super::{StateKey, TranspositionStats, TranspositionTable};
+
+    #[test]
+    fn dead_states() {
+        let domino = Piece::new(1, vec![Position::new(0, 0), Position::new(1, 0)]);
+        let mut left = Board::new(4, 1);
+        left.place_piece(Position::new(0, 0), &domino);
+        let mut other_id = Board::new(4, 1);
+        other_id.place_piece(
+            Position::new(0, 0),
+            &Piece::new(2, domino.points().to_vec()),
+        );
+        let mut right = Board::new(4, 1);
+        right.place_piece(Position::new(2, 0), &domino);
+
+        let mut stats = TranspositionStats::default();
+        let mut table = TranspositionTable::new(1);
+        table.mark_dead(StateKey::new(&left, 0b10), &mut stats);
+        // The ids of the pieces on the board do not matter, only the covered fields
+        assert!(table.is_dead(&StateKey::new(&other_id, 0b10), &mut stats));
+        assert!(!table.is_dead(&StateKey::new(&left, 0b01), &mut stats));
+
+        table.mark_dead(StateKey::new(&right, 0b10), &mut stats);
+        assert_eq!(table.len(), 1);
+        assert!(!table.is_dead(&StateKey::new(&left, 0b10), &mut stats));
+        assert!(table.is_dead(&StateKey::new(&right, 0b10), &mut stats));
+        let expected = TranspositionStats {
+            hits: 2,
+            misses: 2,
+            evictions: 1,
+        };
+        assert_eq!(stats, expected);
+    }
+}
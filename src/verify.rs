@@ -0,0 +1,489 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    board::Board,
+    piece::Piece,
+    position::Position,
+    solution::{Placement, Solution},
+};
+
+/// A solution to check with [verify]
+#[derive(Clone, Copy, Debug)]
+pub enum Claim<'a> {
+    /// A board with the id of the covering piece in every field
+    Board(&'a Board),
+    /// The placement of every piece
+    Placements(&'a [Placement]),
+}
+
+impl<'a> From<&'a Board> for Claim<'a> {
+    fn from(board: &'a Board) -> Self {
+        Claim::Board(board)
+    }
+}
+
+impl<'a> From<&'a [Placement]> for Claim<'a> {
+    fn from(placements: &'a [Placement]) -> Self {
+        Claim::Placements(placements)
+    }
+}
+
+impl<'a> From<&'a Solution> for Claim<'a> {
+    fn from(solution: &'a Solution) -> Self {
+        Claim::Placements(&solution.placements)
+    }
+}
+
+/// Why a claimed solution is wrong
+#[derive(Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The claimed board has a different width or height than the board
+    SizeMismatch { expected: (u8, u8), found: (u8, u8) },
+    /// A field is blocked on only one of the boards
+    BlockedMismatch(Position),
+    /// A field that no piece covers
+    Uncovered(Position),
+    /// A field that more than one piece covers
+    Overlap(Position),
+    /// A piece covers a blocked field
+    CoversBlocked { piece: u8, position: Position },
+    /// A piece covers a position that is not on the board
+    OutsideBoard { piece: u8, position: Position },
+    /// An id that none of the pieces has
+    UnknownPiece(u8),
+    /// A transform index beyond the transforms of the piece
+    UnknownTransform { piece: u8, transform: usize },
+    /// The cells of a placement are not the transform placed at the anchor
+    CellsMismatch { piece: u8 },
+    /// A piece is placed a different number of times than it is given
+    WrongCount {
+        piece: u8,
+        expected: usize,
+        found: usize,
+    },
+    /// The fields with the id of a piece are more or less than its copies cover
+    WrongArea {
+        piece: u8,
+        expected: usize,
+        found: usize,
+    },
+    /// The fields with the id of a piece can not be covered by the transforms of the piece
+    NotCongruent { piece: u8 },
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fmt_position = |p: &Position| format!("({}, {})", p.x(), p.y());
+        match self {
+            VerifyError::SizeMismatch { expected, found } => write!(
+                f,
+                "Board is {}x{}, but should be {}x{}",
+                found.0, found.1, expected.0, expected.1
+            ),
+            VerifyError::BlockedMismatch(p) => {
+                write!(f, "Field {} is blocked on only one board", fmt_position(p))
+            }
+            VerifyError::Uncovered(p) => write!(f, "Field {} is not covered", fmt_position(p)),
+            VerifyError::Overlap(p) => {
+                write!(f, "Field {} is covered more than once", fmt_position(p))
+            }
+            VerifyError::CoversBlocked { piece, position } => write!(
+                f,
+                "Piece {piece} covers the blocked field {}",
+                fmt_position(position)
+            ),
+            VerifyError::OutsideBoard { piece, position } => write!(
+                f,
+                "Piece {piece} covers {}, which is outside of the board",
+                fmt_position(position)
+            ),
+            VerifyError::UnknownPiece(piece) => write!(f, "There is no piece {piece}"),
+            VerifyError::UnknownTransform { piece, transform } => {
+                write!(f, "Piece {piece} has no transform {transform}")
+            }
+            VerifyError::CellsMismatch { piece } => write!(
+                f,
+                "The cells of piece {piece} do not match its transform and anchor"
+            ),
+            VerifyError::WrongCount {
+                piece,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Piece {piece} is used {found} times, but should be used {expected} times"
+            ),
+            VerifyError::WrongArea {
+                piece,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Piece {piece} covers {found} fields, but should cover {expected} fields"
+            ),
+            VerifyError::NotCongruent { piece } => {
+                write!(f, "The fields of piece {piece} do not have its shape")
+            }
+        }
+    }
+}
+
+/// Checks that the claim solves the board with the pieces, independent of any solver.
+///
+/// The board is the empty puzzle board, only its size and blocked fields are used.
+/// Every free field has to be covered exactly once, every copy of a piece has to be used once with its own id,
+/// and the fields of every copy have to form one of the [Piece::get_all_transforms] of its piece.
+/// Transform indices of placements refer to [Piece::get_all_transforms].
+pub fn verify<'a>(
+    board: &Board,
+    pieces: &[Piece],
+    claim: impl Into<Claim<'a>>,
+) -> Result<(), VerifyError> {
+    let mut shapes: HashMap<u8, Vec<Vec<Piece>>> = HashMap::new();
+    for piece in pieces {
+        let all_transforms = piece.get_all_transforms();
+        for id in piece.ids() {
+            shapes.entry(id).or_default().push(all_transforms.clone());
+        }
+    }
+    match claim.into() {
+        Claim::Board(claimed) => verify_board(board, &shapes, claimed),
+        Claim::Placements(placements) => verify_placements(board, &shapes, placements),
+    }
+}
+
+fn verify_board(
+    board: &Board,
+    shapes: &HashMap<u8, Vec<Vec<Piece>>>,
+    claimed: &Board,
+) -> Result<(), VerifyError> {
+    if (claimed.width(), claimed.height()) != (board.width(), board.height()) {
+        return Err(VerifyError::SizeMismatch {
+            expected: (board.width(), board.height()),
+            found: (claimed.width(), claimed.height()),
+        });
+    }
+
+    let mut fields: HashMap<u8, u64> = HashMap::new();
+    for (index, position) in board.positions().enumerate() {
+        if board.is_blocked(position) != claimed.is_blocked(position) {
+            return Err(VerifyError::BlockedMismatch(position));
+        }
+        match claimed.get_value(position) {
+            0 if !board.is_blocked(position) => return Err(VerifyError::Uncovered(position)),
+            0 => {}
+            // Blocked fields of the claimed board are never covered
+            id => *fields.entry(id).or_default() |= 1 << index,
+        }
+    }
+
+    let unknown = fields.keys().filter(|id| !shapes.contains_key(id));
+    if let Some(id) = unknown.min() {
+        return Err(VerifyError::UnknownPiece(*id));
+    }
+
+    let mut ids = shapes.keys().copied().collect::<Vec<_>>();
+    ids.sort();
+    if let Some(id) = ids.iter().find(|id| !fields.contains_key(id)) {
+        return Err(VerifyError::WrongCount {
+            piece: *id,
+            expected: shapes[id].len(),
+            found: 0,
+        });
+    }
+    for id in ids {
+        let all_shapes = &shapes[&id];
+        let mask = fields[&id];
+        let expected = all_shapes
+            .iter()
+            .map(|transforms| transforms[0].points().len())
+            .sum::<usize>();
+        let found = mask.count_ones() as usize;
+        if found != expected {
+            return Err(VerifyError::WrongArea {
+                piece: id,
+                expected,
+                found,
+            });
+        }
+        let mut used = vec![false; all_shapes.len()];
+        if !tile(board, mask, all_shapes, &mut used) {
+            return Err(VerifyError::NotCongruent { piece: id });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the fields of the mask can be covered by placing every shape exactly once
+fn tile(board: &Board, mask: u64, shapes: &[Vec<Piece>], used: &mut [bool]) -> bool {
+    if mask == 0 {
+        return used.iter().all(|u| *u);
+    }
+    // The first field has to be covered by one of the points of some shape
+    let cell = board.position(mask.trailing_zeros() as usize);
+    for (index, all_transforms) in shapes.iter().enumerate() {
+        if used[index] {
+            continue;
+        }
+        for shape in all_transforms {
+            for point in shape.points() {
+                let (Some(x), Some(y)) = (
+                    cell.x().checked_sub(point.x()),
+                    cell.y().checked_sub(point.y()),
+                ) else {
+                    continue;
+                };
+                let Some(piece_mask) = board.piece_mask(Position::new(x, y), shape) else {
+                    continue;
+                };
+                if piece_mask & !mask != 0 {
+                    continue;
+                }
+                used[index] = true;
+                if tile(board, mask & !piece_mask, shapes, used) {
+                    return true;
+                }
+                used[index] = false;
+            }
+        }
+    }
+    false
+}
+
+/// Sorts the cells, so placements compare equal no matter in which order they list them
+fn sorted(mut cells: Vec<Position>) -> Vec<Position> {
+    cells.sort();
+    cells
+}
+
+fn verify_placements(
+    board: &Board,
+    shapes: &HashMap<u8, Vec<Vec<Piece>>>,
+    placements: &[Placement],
+) -> Result<(), VerifyError> {
+    let mut counts: HashMap<u8, usize> = HashMap::new();
+    let mut covered = *board;
+    for placement in placements {
+        let piece = placement.piece;
+        let Some(all_shapes) = shapes.get(&piece) else {
+            return Err(VerifyError::UnknownPiece(piece));
+        };
+        let expected = all_shapes
+            .iter()
+            .filter_map(|transforms| transforms.get(placement.transform))
+            .map(|shape| {
+                Placement::new(shape, placement.transform, placement.anchor)
+                    .map(|p| sorted(p.cells))
+            })
+            .collect::<Vec<_>>();
+        if expected.is_empty() {
+            return Err(VerifyError::UnknownTransform {
+                piece,
+                transform: placement.transform,
+            });
+        }
+        // The cells can be listed in any order
+        if !expected.contains(&Some(sorted(placement.cells.clone()))) {
+            return Err(VerifyError::CellsMismatch { piece });
+        }
+
+        for &position in placement.cells.iter() {
+            if !board.contains(position) {
+                return Err(VerifyError::OutsideBoard { piece, position });
+            }
+            if board.is_blocked(position) {
+                return Err(VerifyError::CoversBlocked { piece, position });
+            }
+            if covered.get_value(position) != 0 {
+                return Err(VerifyError::Overlap(position));
+            }
+            covered.set_value(position, piece);
+        }
+        *counts.entry(piece).or_default() += 1;
+    }
+
+    let mut ids = shapes.keys().copied().collect::<Vec<_>>();
+    ids.sort();
+    for id in ids {
+        let expected = shapes[&id].len();
+        let found = counts.get(&id).copied().unwrap_or(0);
+        if found != expected {
+            return Err(VerifyError::WrongCount {
+                piece: id,
+                expected,
+                found,
+            });
+        }
+    }
+    if let Some(position) = board.positions().find(|p| covered.is_free(*p)) {
+        return Err(VerifyError::Uncovered(position));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::Board,
+        piece::Piece,
+        position::Position,
+        solution::Placement,
+        solve::{dlx_solver::DlxSolver, solve},
+    };
+
+    use super::{verify, VerifyError};
+
+    /// An L tromino and a domino, filling a 3x2 board with one blocked field
+    fn puzzle() -> (Board, Vec<Piece>) {
+        let mut board = Board::new(3, 2);
+        board.block(Position::new(2, 0));
+        let points =
+            |points: &[(u8, u8)]| points.iter().map(|(x, y)| Position::new(*x, *y)).collect();
+        let pieces = vec![
+            Piece::new(1, points(&[(0, 0), (1, 0), (0, 1)])),
+            Piece::new(2, points(&[(0, 0), (1, 0)])),
+        ];
+        (board, pieces)
+    }
+
+    #[test]
+    fn accepts_solutions() {
+        let (board, pieces) = puzzle();
+        let all = pieces
+            .iter()
+            .map(Piece::get_all_transforms)
+            .collect::<Vec<_>>();
+        let solution = solve(DlxSolver, &board, &all).0.ok().unwrap();
+        assert_eq!(verify(&board, &pieces, &solution), Ok(()));
+        assert_eq!(verify(&board, &pieces, &solution.board), Ok(()));
+    }
+
+    #[test]
+    fn rejects_boards() {
+        let (board, pieces) = puzzle();
+        let fill = |ids: &[(u8, u8, u8)]| {
+            let mut claimed = board;
+            for (x, y, id) in ids {
+                claimed.set_value(Position::new(*x, *y), *id);
+            }
+            claimed
+        };
+        // 1 1 #
+        // 1 2 2
+        let valid = [(0, 0, 1), (1, 0, 1), (0, 1, 1), (1, 1, 2), (2, 1, 2)];
+        assert_eq!(verify(&board, &pieces, &fill(&valid)), Ok(()));
+        assert_eq!(
+            verify(&board, &pieces, &fill(&valid[..4])),
+            Err(VerifyError::Uncovered(Position::new(2, 1)))
+        );
+        // The fields of the domino are not connected
+        let split = [(0, 0, 1), (1, 0, 1), (1, 1, 1), (0, 1, 2), (2, 1, 2)];
+        assert_eq!(
+            verify(&board, &pieces, &fill(&split)),
+            Err(VerifyError::NotCongruent { piece: 2 })
+        );
+        let unknown = [(0, 0, 1), (1, 0, 1), (0, 1, 1), (1, 1, 3), (2, 1, 3)];
+        assert_eq!(
+            verify(&board, &pieces, &fill(&unknown)),
+            Err(VerifyError::UnknownPiece(3))
+        );
+        let missing = [(0, 0, 1), (1, 0, 1), (0, 1, 1), (1, 1, 1), (2, 1, 1)];
+        assert_eq!(
+            verify(&board, &pieces, &fill(&missing)),
+            Err(VerifyError::WrongCount {
+                piece: 2,
+                expected: 1,
+                found: 0
+            })
+        );
+        let large = [(0, 0, 1), (1, 0, 1), (0, 1, 1), (1, 1, 1), (2, 1, 2)];
+        assert_eq!(
+            verify(&board, &pieces, &fill(&large)),
+            Err(VerifyError::WrongArea {
+                piece: 1,
+                expected: 3,
+                found: 4
+            })
+        );
+        // A board can not cover its blocked fields, so the claimed board has to leave out the block
+        let mut unblocked = Board::new(3, 2);
+        for (x, y, id) in valid.iter().chain(&[(2, 0, 2)]) {
+            unblocked.set_value(Position::new(*x, *y), *id);
+        }
+        assert_eq!(
+            verify(&board, &pieces, &unblocked),
+            Err(VerifyError::BlockedMismatch(Position::new(2, 0)))
+        );
+        assert!(matches!(
+            verify(&board, &pieces, &Board::new(2, 3)),
+            Err(VerifyError::SizeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_placements() {
+        let (board, pieces) = puzzle();
+        let tromino = &pieces[0].get_all_transforms()[0];
+        let domino = pieces[1].get_all_transforms();
+        let l = Placement::new(tromino, 0, Position::new(0, 0)).unwrap();
+        let flat = Placement::new(&domino[0], 0, Position::new(1, 1)).unwrap();
+        assert_eq!(
+            verify(&board, &pieces, &[l.clone(), flat.clone()][..]),
+            Ok(())
+        );
+        // Placements from other sources can list the cells in any order
+        let mut reordered = l.clone();
+        reordered.cells.reverse();
+        assert_ne!(reordered.cells, l.cells);
+        assert_eq!(
+            verify(&board, &pieces, &[reordered, flat.clone()][..]),
+            Ok(())
+        );
+
+        let upright = Placement::new(&domino[1], 1, Position::new(2, 0)).unwrap();
+        assert_eq!(
+            verify(&board, &pieces, &[l.clone(), upright][..]),
+            Err(VerifyError::CoversBlocked {
+                piece: 2,
+                position: Position::new(2, 0)
+            })
+        );
+        let below = Placement::new(&domino[1], 1, Position::new(1, 1)).unwrap();
+        assert_eq!(
+            verify(&board, &pieces, &[l.clone(), below][..]),
+            Err(VerifyError::OutsideBoard {
+                piece: 2,
+                position: Position::new(1, 2)
+            })
+        );
+        let overlap = Placement::new(&domino[0], 0, Position::new(0, 1)).unwrap();
+        assert_eq!(
+            verify(&board, &pieces, &[l.clone(), overlap][..]),
+            Err(VerifyError::Overlap(Position::new(0, 1)))
+        );
+        let mut moved = flat.clone();
+        moved.anchor = Position::new(0, 1);
+        assert_eq!(
+            verify(&board, &pieces, &[l.clone(), moved][..]),
+            Err(VerifyError::CellsMismatch { piece: 2 })
+        );
+        let mut unknown = flat.clone();
+        unknown.transform = domino.len();
+        assert!(matches!(
+            verify(&board, &pieces, &[l.clone(), unknown][..]),
+            Err(VerifyError::UnknownTransform { piece: 2, .. })
+        ));
+        assert_eq!(
+            verify(&board, &pieces, &[flat.clone()][..]),
+            Err(VerifyError::WrongCount {
+                piece: 1,
+                expected: 1,
+                found: 0
+            })
+        );
+        assert_eq!(
+            verify(&board, &pieces, &[l.clone(), flat, l][..]),
+            Err(VerifyError::Overlap(Position::new(0, 0)))
+        );
+    }
+}
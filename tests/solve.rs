@@ -0,0 +1,167 @@
+use tetris_2::{
+    get_game_pieces,
+    polyomino::{pentomino_name, pentominoes},
+    solve::{
+        break_symmetry, count_distinct_solutions, count_solutions, dlx_solver::DlxSolver,
+        dumb_solver::DumbSolver, solve, solve_all,
+    },
+    verify::verify,
+    Board, Piece, Position, Puzzle, Solution,
+};
+
+fn all_transforms(pieces: &[Piece]) -> Vec<Vec<Piece>> {
+    pieces.iter().map(|p| p.get_all_transforms()).collect()
+}
+
+fn load(input: &str) -> (Board, Vec<Vec<Piece>>) {
+    let puzzle: Puzzle = input.parse().expect("Puzzle file is valid");
+    (puzzle.board, all_transforms(&puzzle.pieces))
+}
+
+/// Checks that every piece covers as many fields as it has points, and is placed where the board shows it
+fn assert_uses_every_piece(solution: &Solution, pieces: &[Vec<Piece>]) {
+    let board = &solution.board;
+    assert!(board.is_solved());
+    assert_eq!(solution.placements.len(), pieces.len());
+    for (all_transforms, placement) in pieces.iter().zip(solution.placements.iter()) {
+        let piece = &all_transforms[0];
+        let fields = board.as_slice().iter().filter(|v| **v == piece.id());
+        assert_eq!(fields.count(), piece.points().len());
+
+        assert_eq!(placement.piece, piece.id());
+        let shape = &all_transforms[placement.transform];
+        assert_eq!(placement.cells.len(), shape.points().len());
+        assert!(placement
+            .cells
+            .iter()
+            .all(|cell| board.get_value(*cell) == piece.id()));
+    }
+}
+
+#[test]
+fn solvers_solve_game() {
+    let board = Board::default();
+    let pieces = all_transforms(&get_game_pieces());
+
+    let (result, _) = solve(DlxSolver, &board, &pieces);
+    assert_uses_every_piece(&result.ok().expect("DLX solves the game"), &pieces);
+
+    let (result, stats) = solve(DumbSolver::default(), &board, &pieces);
+    assert_uses_every_piece(&result.ok().expect("Dumb solves the game"), &pieces);
+    assert!(stats.num_checked_boards > 0);
+}
+
+#[test]
+fn unsolvable_puzzle() {
+    let (board, pieces) = load("[board]\nXXX\nXXX\n[piece]\nXX\n[piece]\nXXX\n");
+    assert!(solve(DlxSolver, &board, &pieces).0.is_err());
+    assert!(solve(DumbSolver::default(), &board, &pieces).0.is_err());
+    assert_eq!(count_solutions(DlxSolver, &board, &pieces, None).0, 0);
+}
+
+#[test]
+fn enumerate_pentomino_rectangle() {
+    let puzzle: Puzzle = include_str!("../puzzles/pentomino_20x3.txt")
+        .parse()
+        .expect("Puzzle file is valid");
+    let (board, pieces) = (puzzle.board, all_transforms(&puzzle.pieces));
+
+    let solutions = solve_all(DlxSolver, &board, &pieces).collect::<Vec<_>>();
+    assert_eq!(solutions.len(), 8);
+    for solution in solutions.iter() {
+        assert_uses_every_piece(solution, &pieces);
+        assert_eq!(verify(&board, &puzzle.pieces, solution), Ok(()));
+        assert_eq!(verify(&board, &puzzle.pieces, &solution.board), Ok(()));
+    }
+    assert_eq!(count_solutions(DlxSolver, &board, &pieces, Some(3)).0, 3);
+    assert_eq!(
+        count_distinct_solutions(DlxSolver, &board, &pieces, None).0,
+        2
+    );
+
+    let restricted = break_symmetry(&board, &pieces).expect("Rectangle is symmetric");
+    assert_eq!(count_solutions(DlxSolver, &board, &restricted, None).0, 2);
+}
+
+#[test]
+fn generated_pentominoes() {
+    let input = include_str!("../puzzles/pentomino_20x3.txt");
+    let drawn: Puzzle = input.parse().expect("Puzzle file is valid");
+    let board = input.split("[piece]").next().expect("Board comes first");
+    let generated: Puzzle = format!("{board}[polyominoes size=5]")
+        .parse()
+        .expect("Puzzle is valid");
+    assert_eq!(generated.board, drawn.board);
+    assert_eq!(generated.pieces.len(), 12);
+
+    // The drawn pieces are the pentominoes in the order of their names
+    let names = drawn
+        .pieces
+        .iter()
+        .map(|p| pentomino_name(p.points()).expect("Piece is a pentomino"))
+        .collect::<String>();
+    assert_eq!(names, "FILNPTUVWXYZ");
+    assert_eq!(
+        all_transforms(&drawn.pieces),
+        all_transforms(&pentominoes())
+    );
+
+    let pieces = all_transforms(&generated.pieces);
+    let solutions = solve_all(DlxSolver, &generated.board, &pieces).collect::<Vec<_>>();
+    assert_eq!(solutions.len(), 8);
+    for solution in solutions.iter() {
+        assert_eq!(
+            verify(&generated.board, &generated.pieces, solution),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn pentomino_square_with_hole() {
+    let (board, pieces) = load(include_str!("../puzzles/pentomino_hole.txt"));
+    assert!(board.is_blocked(Position::new(3, 4)));
+
+    let (result, _) = solve(DlxSolver, &board, &pieces);
+    let solution = result.ok().expect("Square with hole is solvable");
+    assert_uses_every_piece(&solution, &pieces);
+    assert_eq!(solution.board.blocked(), board.blocked());
+}
+
+#[test]
+fn one_sided_pentomino_rectangle() {
+    let input = include_str!("../puzzles/pentomino_20x3.txt");
+    let free: Puzzle = input.parse().expect("Puzzle file is valid");
+    // Only the F pentomino can not be flipped over
+    let one_sided: Puzzle = input
+        .replacen("[piece]", "[piece orientation=one-sided]", 1)
+        .parse()
+        .expect("Puzzle file is valid");
+    let pieces = all_transforms(&one_sided.pieces);
+    assert_eq!(pieces[0].len(), 4);
+
+    // The free solutions, that only use allowed orientations
+    let allowed = solve_all(DlxSolver, &free.board, &all_transforms(&free.pieces))
+        .filter(|solution| {
+            let pieces = free.pieces.iter().zip(one_sided.pieces.iter());
+            solution
+                .placements
+                .iter()
+                .zip(pieces)
+                .all(|(p, (free, one_sided))| {
+                    let orientation = p.orientation(free).expect("Transform exists");
+                    one_sided.policy().allows(orientation)
+                })
+        })
+        .count();
+    assert!(allowed > 0);
+
+    let solutions = solve_all(DlxSolver, &one_sided.board, &pieces).collect::<Vec<_>>();
+    assert_eq!(solutions.len(), allowed);
+    for solution in solutions.iter() {
+        assert_eq!(
+            verify(&one_sided.board, &one_sided.pieces, solution),
+            Ok(())
+        );
+    }
+}